@@ -0,0 +1,75 @@
+use std::sync::{Arc, RwLock};
+
+use glfw::{Action, Key, WindowEvent};
+use nalgebra::Vector3;
+use rusted_open::{framework::events::movement::rotate_object, CustomShader, EngineResult, FrameworkController, Game, Generic2DGraphicsObject};
+
+const VERTEX_SHADER: &str = r#"
+#version 330 core
+layout(location = 0) in vec2 position;
+layout(location = 1) in vec2 texCoord;
+
+uniform mat4 projection;
+uniform mat4 model;
+
+out vec2 TexCoord;
+
+void main() {
+    gl_Position = projection * model * vec4(position, 0.0, 1.0);
+    TexCoord = texCoord;
+}
+"#;
+
+const FRAGMENT_SHADER: &str = r#"
+#version 330 core
+in vec2 TexCoord;
+out vec4 FragColor;
+
+void main() {
+    FragColor = vec4(TexCoord, 0.5, 1.0);
+}
+"#;
+
+struct RotatingQuad;
+
+impl Game for RotatingQuad {
+    fn init(&mut self, controller: &mut FrameworkController) -> EngineResult<()> {
+        // A unit quad with texture coordinates, drawn as a triangle fan
+        let shader = CustomShader::new(VERTEX_SHADER, FRAGMENT_SHADER)?;
+        let quad = Generic2DGraphicsObject::new(
+            "quad".to_string(),
+            vec![0.5, 0.5, 0.5, -0.5, -0.5, -0.5, -0.5, 0.5],
+            vec![1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0],
+            shader.get_shader_program(),
+            Vector3::new(0.0, 0.0, 0.0),
+            0.0,
+            0.5,
+            None,
+            None,
+            None,
+        );
+        controller.get_master_graphics_list().read().unwrap().add_object(Arc::new(RwLock::new(quad)))?;
+        Ok(())
+    }
+
+    fn handle_event(&mut self, _controller: &mut FrameworkController, window: &mut glfw::PWindow, event: &WindowEvent) {
+        if let WindowEvent::Key(Key::Escape, _, Action::Press, _) = event {
+            window.set_should_close(true);
+        }
+    }
+
+    fn update(&mut self, controller: &mut FrameworkController, delta_time: f32) {
+        if let Some(quad) = controller.get_master_graphics_list().read().unwrap().get_object("quad") {
+            rotate_object(&mut quad.write().unwrap(), delta_time);
+        }
+    }
+}
+
+fn main() -> EngineResult<()> {
+    let (mut framework_controller, mut context) = FrameworkController::builder()
+        .size(640, 480)
+        .title("rusted_open basic example")
+        .build()?;
+
+    framework_controller.run(&mut context.glfw, &mut context.window, &context.events, &mut RotatingQuad)
+}
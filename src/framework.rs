@@ -1,3 +1,17 @@
 pub mod graphics;
 pub mod framework_controller;
-pub mod events;
\ No newline at end of file
+pub mod framework_builder;
+pub mod display;
+pub mod game;
+pub mod events;
+pub mod network;
+pub mod error;
+pub mod asset_manager;
+#[cfg(feature = "hot_reload")]
+pub mod asset_watcher;
+pub mod localization;
+pub mod logger;
+pub mod save_game;
+pub mod settings;
+#[cfg(feature = "scripting")]
+pub mod scripting;
\ No newline at end of file
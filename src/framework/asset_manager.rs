@@ -0,0 +1,229 @@
+use std::{collections::HashMap, fs, path::{Component, Path, PathBuf}};
+
+use gl::types::GLuint;
+
+use super::{error::{EngineError, EngineResult}, graphics::texture_manager::TextureManager, localization::Localization};
+
+const PAK_MAGIC: &[u8; 4] = b"RPAK";
+
+/// A read-only archive of assets, read fully into memory when opened.
+///
+/// Layout: the magic "RPAK", a little-endian u32 entry count, then per entry a u32 name length, the UTF-8 name
+/// (relative, '/' separated), a u64 offset and a u64 length, followed by the file data the offsets point into.
+pub struct PakArchive {
+    path: String,
+    data: Vec<u8>,
+    entries: HashMap<String, (usize, usize)>, // Normalized name -> (offset, length) into data
+}
+
+impl PakArchive {
+    pub fn open(path: &str) -> EngineResult<Self> {
+        let data = fs::read(path).map_err(|e| EngineError::io(path, e))?;
+        let invalid = |reason: &str| EngineError::InvalidPak { path: path.to_owned(), reason: reason.to_owned() };
+
+        if data.len() < 8 || &data[0..4] != PAK_MAGIC {
+            return Err(invalid("missing RPAK header"));
+        }
+
+        let mut cursor = 4;
+        let mut read_bytes = |length: usize| -> EngineResult<&[u8]> {
+            let bytes = data.get(cursor..cursor + length).ok_or_else(|| invalid("truncated entry table"))?;
+            cursor += length;
+            Ok(bytes)
+        };
+
+        let count = u32::from_le_bytes(read_bytes(4)?.try_into().unwrap());
+        let mut entries = HashMap::new();
+        for _ in 0..count {
+            let name_length = u32::from_le_bytes(read_bytes(4)?.try_into().unwrap()) as usize;
+            let name = String::from_utf8(read_bytes(name_length)?.to_vec()).map_err(|_| invalid("entry name is not UTF-8"))?;
+            let offset = u64::from_le_bytes(read_bytes(8)?.try_into().unwrap()) as usize;
+            let length = u64::from_le_bytes(read_bytes(8)?.try_into().unwrap()) as usize;
+            entries.insert(normalize_asset_path(&name), (offset, length));
+        }
+
+        if entries.values().any(|(offset, length)| offset.checked_add(*length).is_none_or(|end| end > data.len())) {
+            return Err(invalid("entry points past the end of the file"));
+        }
+
+        Ok(PakArchive { path: path.to_owned(), data, entries })
+    }
+
+    /// Packs every file under source_dir into a pak at output_path, named by their path relative to source_dir
+    pub fn create(source_dir: &str, output_path: &str) -> EngineResult<()> {
+        let mut files = Vec::new();
+        collect_files(Path::new(source_dir), Path::new(source_dir), &mut files)?;
+
+        let mut table = Vec::new();
+        let mut blob = Vec::new();
+        let header_length = 8 + files.iter().map(|(name, _)| 4 + name.len() + 16).sum::<usize>();
+        for (name, path) in &files {
+            let contents = fs::read(path).map_err(|e| EngineError::io(path.display().to_string(), e))?;
+            table.extend_from_slice(&(name.len() as u32).to_le_bytes());
+            table.extend_from_slice(name.as_bytes());
+            table.extend_from_slice(&((header_length + blob.len()) as u64).to_le_bytes());
+            table.extend_from_slice(&(contents.len() as u64).to_le_bytes());
+            blob.extend_from_slice(&contents);
+        }
+
+        let mut output = Vec::with_capacity(header_length + blob.len());
+        output.extend_from_slice(PAK_MAGIC);
+        output.extend_from_slice(&(files.len() as u32).to_le_bytes());
+        output.extend_from_slice(&table);
+        output.extend_from_slice(&blob);
+        fs::write(output_path, output).map_err(|e| EngineError::io(output_path, e))
+    }
+
+    pub fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(&normalize_asset_path(name))
+    }
+
+    pub fn read(&self, name: &str) -> Option<&[u8]> {
+        self.entries.get(&normalize_asset_path(name)).map(|&(offset, length)| &self.data[offset..offset + length])
+    }
+
+    pub fn get_entry_names(&self) -> Vec<String> {
+        self.entries.keys().cloned().collect()
+    }
+}
+
+/// Resolves asset paths against a configurable root and routes loads to the right manager.
+/// Asset paths are relative and may use either '/' or '\' as separators, so the same strings work on every platform.
+/// Mounted pak archives are searched before the root directory, most recently mounted first, so a patch pak can override files.
+pub struct AssetManager {
+    root: PathBuf,
+    paks: Vec<PakArchive>,
+}
+
+impl AssetManager {
+    pub fn new(root: &str) -> Self {
+        AssetManager {
+            root: PathBuf::from(root),
+            paks: Vec::new(),
+        }
+    }
+
+    pub fn get_root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn set_root(&mut self, root: &str) {
+        self.root = PathBuf::from(root);
+    }
+
+    pub fn mount_pak(&mut self, path: &str) -> EngineResult<()> {
+        self.paks.push(PakArchive::open(path)?);
+        Ok(())
+    }
+
+    /// Unmounts a pak by the path it was mounted from. Returns whether it was mounted.
+    pub fn unmount_pak(&mut self, path: &str) -> bool {
+        let before = self.paks.len();
+        self.paks.retain(|pak| pak.get_path() != path);
+        self.paks.len() != before
+    }
+
+    /// The platform path an asset would have on disk under the root
+    pub fn resolve(&self, asset_path: &str) -> PathBuf {
+        asset_path.split(['/', '\\']).filter(|part| !part.is_empty()).fold(self.root.clone(), |path, part| path.join(part))
+    }
+
+    pub fn exists(&self, asset_path: &str) -> bool {
+        self.paks.iter().any(|pak| pak.contains(asset_path)) || self.resolve(asset_path).is_file()
+    }
+
+    pub fn read_bytes(&self, asset_path: &str) -> EngineResult<Vec<u8>> {
+        if let Some(bytes) = self.paks.iter().rev().find_map(|pak| pak.read(asset_path)) {
+            return Ok(bytes.to_vec());
+        }
+        self.read_loose_bytes(asset_path)
+    }
+
+    /// Reads the file under the root directory, ignoring mounted paks, e.g. to pick up an edit to a file a pak also contains
+    pub fn read_loose_bytes(&self, asset_path: &str) -> EngineResult<Vec<u8>> {
+        let path = self.resolve(asset_path);
+        if !path.is_file() {
+            return Err(EngineError::AssetNotFound(asset_path.to_owned()));
+        }
+        fs::read(&path).map_err(|e| EngineError::io(path.display().to_string(), e))
+    }
+
+    pub fn read_to_string(&self, asset_path: &str) -> EngineResult<String> {
+        let bytes = self.read_bytes(asset_path)?;
+        String::from_utf8(bytes).map_err(|_| EngineError::InvalidFileName(format!("{} is not valid UTF-8", asset_path)))
+    }
+
+    /// Every asset path under a directory, from the root and all mounted paks, without duplicates
+    pub fn list_directory(&self, asset_dir: &str) -> EngineResult<Vec<String>> {
+        let prefix = normalize_asset_path(asset_dir);
+        let mut names: Vec<String> = self.paks
+            .iter()
+            .flat_map(|pak| pak.get_entry_names())
+            .filter(|name| prefix.is_empty() || name.starts_with(&format!("{}/", prefix)))
+            .collect();
+
+        let dir = self.resolve(asset_dir);
+        if dir.is_dir() {
+            let mut files = Vec::new();
+            collect_files(&self.root, &dir, &mut files)?;
+            names.extend(files.into_iter().map(|(name, _)| name));
+        }
+
+        names.sort();
+        names.dedup();
+        Ok(names)
+    }
+
+    pub fn load_texture(&self, texture_manager: &TextureManager, name: &str, asset_path: &str) -> EngineResult<GLuint> {
+        let bytes = self.read_bytes(asset_path)?;
+        texture_manager.load_texture_from_memory(name, &bytes)
+    }
+
+    /// Loads every png/jpg under a directory, named by file name without extension, like TextureManager::load_textures_from_directory
+    pub fn load_textures_from_directory(&self, texture_manager: &TextureManager, asset_dir: &str) -> EngineResult<()> {
+        for asset_path in self.list_directory(asset_dir)? {
+            let path = Path::new(&asset_path);
+            let is_image = path.extension().is_some_and(|extension| extension == "png" || extension == "jpg" || extension == "jpeg");
+            if let (true, Some(name)) = (is_image, path.file_stem().and_then(|stem| stem.to_str())) {
+                self.load_texture(texture_manager, name, &asset_path)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn load_language(&self, localization: &mut Localization, language: &str, asset_path: &str) -> EngineResult<()> {
+        let contents = self.read_to_string(asset_path)?;
+        localization.load_language_from_str(language, &contents, asset_path)
+    }
+}
+
+/// Turns any mix of separators and ./ into the '/' separated form pak entries are keyed by
+pub(crate) fn normalize_asset_path(asset_path: &str) -> String {
+    asset_path.split(['/', '\\']).filter(|part| !part.is_empty() && *part != ".").collect::<Vec<_>>().join("/")
+}
+
+/// Recursively lists files under dir as (path relative to base with '/' separators, full path)
+fn collect_files(base: &Path, dir: &Path, files: &mut Vec<(String, PathBuf)>) -> EngineResult<()> {
+    let entries = fs::read_dir(dir).map_err(|e| EngineError::io(dir.display().to_string(), e))?;
+    for entry in entries {
+        let path = entry.map_err(|e| EngineError::io(dir.display().to_string(), e))?.path();
+        if path.is_dir() {
+            collect_files(base, &path, files)?;
+        } else if let Ok(relative) = path.strip_prefix(base) {
+            let name = relative
+                .components()
+                .filter_map(|component| match component {
+                    Component::Normal(part) => part.to_str(),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join("/");
+            files.push((name, path));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,150 @@
+use std::{collections::{BTreeSet, HashMap}, path::{Component, Path, PathBuf}, sync::mpsc::{channel, Receiver}};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use super::{asset_manager::{normalize_asset_path, AssetManager}, error::{EngineError, EngineResult}, graphics::texture_manager::TextureManager, localization::Localization};
+
+/// What a changed file is, and for registered assets what it was loaded as
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssetKind {
+    /// A texture registered under this name, reloaded in place
+    Texture(String),
+    /// A language table registered under this language, reloaded into the Localization
+    Language(String),
+    /// An unregistered .vert/.frag/.glsl file, left for the game to recompile
+    Shader,
+    /// Anything else, e.g. scene or audio files, left for the game to handle
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetChange {
+    pub asset_path: String, // Relative to the asset root, '/' separated
+    pub kind: AssetKind,
+}
+
+/// Development-time watcher (feature "hot_reload") that notices files changing under the asset root and reloads them.
+/// Register the assets that should be reloaded automatically, then call reload once per frame.
+pub struct AssetWatcher {
+    root: PathBuf,
+    _watcher: RecommendedWatcher, // Stops watching when dropped
+    receiver: Receiver<notify::Result<Event>>,
+    registered: HashMap<String, AssetKind>, // Asset path -> what to reload it as
+    enabled: bool,
+}
+
+impl AssetWatcher {
+    /// Starts watching the asset manager's root directory recursively
+    pub fn new(asset_manager: &AssetManager) -> EngineResult<Self> {
+        let root = asset_manager.get_root().to_path_buf();
+        let watch_error = |e: notify::Error| EngineError::Watch { path: root.display().to_string(), message: e.to_string() };
+
+        let (sender, receiver) = channel();
+        let mut watcher = notify::recommended_watcher(sender).map_err(watch_error)?;
+        watcher.watch(&root, RecursiveMode::Recursive).map_err(watch_error)?;
+
+        Ok(AssetWatcher {
+            root: root.canonicalize().unwrap_or(root),
+            _watcher: watcher,
+            receiver,
+            registered: HashMap::new(),
+            enabled: true,
+        })
+    }
+
+    /// Reloads the texture at asset_path into name whenever the file changes
+    pub fn watch_texture(&mut self, asset_path: &str, name: &str) {
+        self.registered.insert(normalize_asset_path(asset_path), AssetKind::Texture(name.to_owned()));
+    }
+
+    /// Reloads the language table at asset_path whenever the file changes
+    pub fn watch_language(&mut self, asset_path: &str, language: &str) {
+        self.registered.insert(normalize_asset_path(asset_path), AssetKind::Language(language.to_owned()));
+    }
+
+    pub fn unwatch(&mut self, asset_path: &str) {
+        self.registered.remove(&normalize_asset_path(asset_path));
+    }
+
+    /// While disabled, changes are drained and ignored
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Drains pending file events into one change per file, without reloading anything
+    pub fn poll_changes(&mut self) -> Vec<AssetChange> {
+        let mut changed_paths = BTreeSet::new();
+        for event in self.receiver.try_iter().flatten() {
+            if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                changed_paths.extend(event.paths.iter().filter_map(|path| self.to_asset_path(path)));
+            }
+        }
+
+        if !self.enabled {
+            return Vec::new();
+        }
+
+        changed_paths
+            .into_iter()
+            .map(|asset_path| {
+                let kind = self.registered.get(&asset_path).cloned().unwrap_or_else(|| Self::infer_kind(&asset_path));
+                AssetChange { asset_path, kind }
+            })
+            .collect()
+    }
+
+    /// Reloads changed textures and language tables, and returns every change so the game can handle the rest (shaders, scenes, audio).
+    /// Failed reloads are logged and the previous version is kept, since files are often caught half written.
+    pub fn reload(&mut self, asset_manager: &AssetManager, texture_manager: &TextureManager, mut localization: Option<&mut Localization>) -> Vec<AssetChange> {
+        let changes = self.poll_changes();
+
+        for change in &changes {
+            // The edited file is on disk, a mounted pak would still hand out its old copy
+            let result = match &change.kind {
+                AssetKind::Texture(name) => asset_manager
+                    .read_loose_bytes(&change.asset_path)
+                    .and_then(|bytes| texture_manager.reload_texture_from_memory(name, &bytes))
+                    .map(|_| ()),
+                AssetKind::Language(language) => match localization.as_deref_mut() {
+                    Some(localization) => asset_manager
+                        .read_loose_bytes(&change.asset_path)
+                        .and_then(|bytes| String::from_utf8(bytes).map_err(|_| EngineError::InvalidFileName(format!("{} is not valid UTF-8", change.asset_path))))
+                        .and_then(|contents| localization.load_language_from_str(language, &contents, &change.asset_path)),
+                    None => Ok(()),
+                },
+                AssetKind::Shader | AssetKind::Other => continue,
+            };
+
+            match result {
+                Ok(()) => log::info!("Reloaded '{}'", change.asset_path),
+                Err(e) => log::warn!("Failed to reload '{}': {}", change.asset_path, e),
+            }
+        }
+
+        changes
+    }
+
+    fn to_asset_path(&self, path: &Path) -> Option<String> {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let relative = path.strip_prefix(&self.root).ok()?;
+        let parts: Vec<&str> = relative
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(part) => part.to_str(),
+                _ => None,
+            })
+            .collect();
+        (!parts.is_empty()).then(|| parts.join("/"))
+    }
+
+    fn infer_kind(asset_path: &str) -> AssetKind {
+        match Path::new(asset_path).extension().and_then(|extension| extension.to_str()) {
+            Some("vert" | "frag" | "glsl") => AssetKind::Shader,
+            _ => AssetKind::Other,
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use super::error::{EngineError, EngineResult};
+
+/// How the window occupies the screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayMode {
+    #[default]
+    Windowed,
+    /// An undecorated window covering the whole monitor, at the desktop's video mode
+    Borderless,
+    /// Exclusive fullscreen, optionally switching the monitor to another video mode
+    Fullscreen,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VideoModeInfo {
+    pub width: u32,
+    pub height: u32,
+    pub refresh_rate: u32,
+}
+
+impl From<glfw::VidMode> for VideoModeInfo {
+    fn from(video_mode: glfw::VidMode) -> Self {
+        VideoModeInfo {
+            width: video_mode.width,
+            height: video_mode.height,
+            refresh_rate: video_mode.refresh_rate,
+        }
+    }
+}
+
+/// A connected monitor. index is what DisplayManager::set_display_mode takes to pick it.
+#[derive(Debug, Clone)]
+pub struct MonitorInfo {
+    pub index: usize,
+    pub name: String,
+    pub position: (i32, i32),
+    pub current_mode: Option<VideoModeInfo>,
+    pub video_modes: Vec<VideoModeInfo>,
+}
+
+/// Switches the window between windowed, borderless and exclusive fullscreen at runtime.
+/// Remembers the windowed position and size so switching back restores them.
+/// The GL context survives the switch; the FramebufferSize event that follows updates the viewport and projection.
+pub struct DisplayManager {
+    display_mode: DisplayMode,
+    windowed_rect: (i32, i32, i32, i32), // x, y, width, height to restore when going back to windowed
+}
+
+impl DisplayManager {
+    pub fn new(window: &glfw::PWindow) -> Self {
+        let (x, y) = window.get_pos();
+        let (width, height) = window.get_size();
+        DisplayManager {
+            display_mode: DisplayMode::Windowed,
+            windowed_rect: (x, y, width, height),
+        }
+    }
+
+    /// Lists connected monitors, the primary monitor first
+    pub fn list_monitors(glfw: &mut glfw::Glfw) -> Vec<MonitorInfo> {
+        glfw.with_connected_monitors(|_, monitors| {
+            monitors
+                .iter()
+                .enumerate()
+                .map(|(index, monitor)| MonitorInfo {
+                    index,
+                    name: monitor.get_name().unwrap_or_else(|| format!("Monitor {}", index)),
+                    position: monitor.get_pos(),
+                    current_mode: monitor.get_video_mode().map(VideoModeInfo::from),
+                    video_modes: monitor.get_video_modes().into_iter().map(VideoModeInfo::from).collect(),
+                })
+                .collect()
+        })
+    }
+
+    pub fn get_display_mode(&self) -> DisplayMode {
+        self.display_mode
+    }
+
+    /// Switches display mode on the given monitor. video_mode only applies to Fullscreen; None keeps the monitor's current mode.
+    pub fn set_display_mode(&mut self, glfw: &mut glfw::Glfw, window: &mut glfw::PWindow, display_mode: DisplayMode, monitor_index: usize, video_mode: Option<VideoModeInfo>) -> EngineResult<()> {
+        if self.display_mode == DisplayMode::Windowed && display_mode != DisplayMode::Windowed {
+            let (x, y) = window.get_pos();
+            let (width, height) = window.get_size();
+            self.windowed_rect = (x, y, width, height);
+        }
+
+        let windowed_rect = self.windowed_rect;
+        glfw.with_connected_monitors(|_, monitors| {
+            let monitor = monitors.get(monitor_index).ok_or(EngineError::MonitorNotFound(monitor_index))?;
+            let current_mode = monitor.get_video_mode().ok_or(EngineError::MonitorNotFound(monitor_index))?;
+            let (monitor_x, monitor_y) = monitor.get_pos();
+
+            match display_mode {
+                DisplayMode::Windowed => {
+                    window.set_decorated(true);
+                    let (x, y, width, height) = windowed_rect;
+                    window.set_monitor(glfw::WindowMode::Windowed, x, y, width as u32, height as u32, None);
+                }
+                DisplayMode::Borderless => {
+                    window.set_decorated(false);
+                    window.set_monitor(glfw::WindowMode::Windowed, monitor_x, monitor_y, current_mode.width, current_mode.height, None);
+                }
+                DisplayMode::Fullscreen => {
+                    let mode = video_mode.unwrap_or_else(|| current_mode.into());
+                    window.set_monitor(glfw::WindowMode::FullScreen(monitor), 0, 0, mode.width, mode.height, Some(mode.refresh_rate));
+                }
+            }
+            Ok(())
+        })?;
+
+        self.display_mode = display_mode;
+        Ok(())
+    }
+
+    /// Flips between windowed and the given fullscreen mode on the primary monitor
+    pub fn toggle_fullscreen(&mut self, glfw: &mut glfw::Glfw, window: &mut glfw::PWindow, fullscreen_mode: DisplayMode) -> EngineResult<()> {
+        let next = if self.display_mode == DisplayMode::Windowed { fullscreen_mode } else { DisplayMode::Windowed };
+        self.set_display_mode(glfw, window, next, 0, None)
+    }
+}
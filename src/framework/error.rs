@@ -0,0 +1,100 @@
+use thiserror::Error;
+
+/// Every error the framework can return, so the host application can decide how to degrade instead of aborting
+#[derive(Debug, Error)]
+pub enum EngineError {
+    #[error("Window creation failed: {0}")]
+    WindowCreation(String),
+
+    #[error("No monitor at index {0}")]
+    MonitorNotFound(usize),
+
+    #[error("OpenGL initialization failed: {0}")]
+    GlInit(String),
+
+    #[error("Framebuffer creation failed: {0}")]
+    Framebuffer(String),
+
+    #[error("Shader compilation failed: {0}")]
+    ShaderCompile(String),
+
+    #[error("Shader program linking failed: {0}")]
+    ShaderLink(String),
+
+    #[error("Failed to load image '{path}': {source}")]
+    Image { path: String, #[source] source: image::ImageError },
+
+    #[error("I/O error on '{path}': {source}")]
+    Io { path: String, #[source] source: std::io::Error },
+
+    #[error("Failed to parse '{path}': {source}")]
+    Parse { path: String, #[source] source: serde_json::Error },
+
+    #[error("Failed to serialize data: {0}")]
+    Serialize(#[source] serde_json::Error),
+
+    #[error("Asset '{0}' was not found under the asset root or in any mounted pak")]
+    AssetNotFound(String),
+
+    #[error("Invalid pak archive '{path}': {reason}")]
+    InvalidPak { path: String, reason: String },
+
+    #[error("Failed to watch '{path}': {message}")]
+    Watch { path: String, message: String },
+
+    #[error("Network error: {0}")]
+    Network(String),
+
+    #[error("Rollback player {local_player} is out of range for a {num_players} player session")]
+    InvalidRollbackPlayer { local_player: usize, num_players: usize },
+
+    #[error("Can't roll back to tick {0}, its snapshot is no longer kept, so the session has desynced")]
+    RollbackSnapshotLost(u32),
+
+    #[error("Invalid tilemap '{name}': {reason}")]
+    InvalidTilemap { name: String, reason: String },
+
+    #[error("Invalid file name '{0}'")]
+    InvalidFileName(String),
+
+    #[error("An object named '{0}' already exists")]
+    DuplicateObject(String),
+
+    #[error("No object named '{0}'")]
+    ObjectNotFound(String),
+
+    #[error("Key '{key}' is already bound to '{action}'")]
+    KeyBindingConflict { key: String, action: String },
+
+    #[error("Language '{0}' is not loaded")]
+    LanguageNotLoaded(String),
+
+    #[error("Save slot '{slot}' is from a newer version ({version})")]
+    SaveVersion { slot: String, version: u32 },
+
+    #[error("Invalid save slot name '{0}', slot names can't be empty or contain path separators or '..'")]
+    InvalidSaveSlot(String),
+
+    #[error("Save thread panicked")]
+    SaveThreadPanicked,
+
+    #[error("A logger is already installed")]
+    LoggerAlreadyInstalled,
+
+    #[error("Script error for '{object}': {message}")]
+    Script { object: String, message: String },
+}
+
+impl EngineError {
+    /// Wraps an I/O error with the path it happened on
+    pub fn io(path: impl Into<String>, source: std::io::Error) -> Self {
+        EngineError::Io { path: path.into(), source }
+    }
+
+    /// Wraps a JSON parse error with the path of the file being parsed
+    pub fn parse(path: impl Into<String>, source: serde_json::Error) -> Self {
+        EngineError::Parse { path: path.into(), source }
+    }
+}
+
+pub type EngineResult<T> = Result<T, EngineError>;
@@ -1 +1,9 @@
-pub mod movement;
\ No newline at end of file
+pub mod movement;
+pub mod path_follower;
+pub mod pathfinding;
+pub mod steering;
+pub mod fixed_timestep;
+pub mod hitstop;
+pub mod timers;
+pub mod sequencer;
+pub mod state_machine;
\ No newline at end of file
@@ -0,0 +1,59 @@
+pub struct FixedTimestep {
+    step_duration: f32, // Seconds per fixed tick
+    max_steps: usize, // Cap on catch-up ticks per frame so a long stall can't spiral
+    accumulator: f32,
+}
+
+impl FixedTimestep {
+    /// Creates a fixed timestep running at the given rate (ticks per second)
+    pub fn new(hz: f32, max_steps: usize) -> Self {
+        FixedTimestep {
+            step_duration: 1.0 / hz,
+            max_steps,
+            accumulator: 0.0,
+        }
+    }
+
+    /// Adds the frame time to the accumulator and returns how many fixed ticks should run this frame
+    pub fn accumulate(&mut self, delta_time: f32) -> usize {
+        self.accumulator += delta_time;
+
+        let mut steps = (self.accumulator / self.step_duration).floor() as usize;
+        if steps > self.max_steps {
+            // Too far behind, drop the backlog rather than trying to simulate all of it
+            steps = self.max_steps;
+            self.accumulator = 0.0;
+        } else {
+            self.accumulator -= steps as f32 * self.step_duration;
+        }
+        steps
+    }
+
+    /// Runs the closure once per fixed tick owed this frame, passing the fixed step duration
+    pub fn run<F: FnMut(f32)>(&mut self, delta_time: f32, mut step: F) {
+        for _ in 0..self.accumulate(delta_time) {
+            step(self.step_duration);
+        }
+    }
+
+    /// How far (0.0 - 1.0) the current frame is between the last tick and the next, for interpolating rendered positions
+    pub fn get_alpha(&self) -> f32 {
+        (self.accumulator / self.step_duration).clamp(0.0, 1.0)
+    }
+
+    pub fn get_step_duration(&self) -> f32 {
+        self.step_duration
+    }
+
+    pub fn set_hz(&mut self, hz: f32) {
+        self.step_duration = 1.0 / hz;
+    }
+
+    pub fn set_max_steps(&mut self, max_steps: usize) {
+        self.max_steps = max_steps;
+    }
+
+    pub fn reset(&mut self) {
+        self.accumulator = 0.0;
+    }
+}
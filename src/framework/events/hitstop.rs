@@ -0,0 +1,48 @@
+pub struct Hitstop {
+    remaining: f32, // Seconds of freeze left, counted down in real (unscaled) time
+}
+
+impl Hitstop {
+    pub fn new() -> Self {
+        Hitstop {
+            remaining: 0.0,
+        }
+    }
+
+    /// Freezes gameplay time for the given duration. Overlapping hits extend to the longest freeze rather than stacking.
+    pub fn hitstop(&mut self, duration: f32) {
+        self.remaining = self.remaining.max(duration);
+    }
+
+    /// Counts down the freeze and returns the delta time gameplay should use this frame.
+    /// Pass the real delta time in, and hand the result only to gameplay (movement, animation), not UI.
+    pub fn apply(&mut self, delta_time: f32) -> f32 {
+        if self.remaining <= 0.0 {
+            return delta_time;
+        }
+
+        if delta_time <= self.remaining {
+            self.remaining -= delta_time;
+            0.0
+        } else {
+            // The freeze ends partway through this frame, let the leftover time through
+            let leftover = delta_time - self.remaining;
+            self.remaining = 0.0;
+            leftover
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.remaining > 0.0
+    }
+
+    pub fn cancel(&mut self) {
+        self.remaining = 0.0;
+    }
+}
+
+impl Default for Hitstop {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,259 @@
+use nalgebra::Vector3;
+use serde::{Deserialize, Serialize};
+use crate::framework::graphics::internal_object::graphics_object::Generic2DGraphicsObject;
+
+#[derive(Serialize, Debug, Clone, Deserialize)]
+pub struct PathConfig {
+    pub waypoints: Vec<[f32; 3]>,
+    pub speed: f32, // World units per second
+    pub easing: String, // "linear", "ease_in", "ease_out" or "ease_in_out", applied per segment
+    pub mode: String, // "once", "loop" or "ping_pong"
+}
+
+pub struct PathFollower {
+    config: PathConfig,
+    current_segment: usize,
+    segment_progress: f32, // 0.0 - 1.0 along the current segment
+    reversed: bool, // Travelling back towards the first waypoint (ping_pong only)
+    finished: bool,
+}
+
+impl PathFollower {
+    pub fn new(config: PathConfig) -> Self {
+        PathFollower {
+            config,
+            current_segment: 0,
+            segment_progress: 0.0,
+            reversed: false,
+            finished: false,
+        }
+    }
+
+    /// Advances along the path and moves the object to its new position
+    pub fn update(&mut self, object: &mut Generic2DGraphicsObject, delta_time: f32) {
+        if let Some(position) = self.advance(delta_time) {
+            object.set_position(position);
+        }
+    }
+
+    /// Advances along the path by delta_time and returns the new position, or None if there is nowhere to go
+    pub fn advance(&mut self, delta_time: f32) -> Option<Vector3<f32>> {
+        let waypoint_count = self.config.waypoints.len();
+        if waypoint_count == 0 {
+            return None;
+        }
+        if waypoint_count == 1 {
+            self.finished = true;
+            return Some(self.waypoint(0));
+        }
+
+        let path_length = self.path_length();
+        if path_length <= f32::EPSILON {
+            return Some(self.current_position()); // Every waypoint is in the same place, there is nowhere to move to
+        }
+
+        let mut remaining = self.config.speed * delta_time;
+        // A repeating path ends up where it started after each full cycle, so only the part of a cycle left over matters
+        match self.config.mode.as_str() {
+            "loop" => remaining %= path_length,
+            "ping_pong" => remaining %= 2.0 * path_length,
+            _ => {}
+        }
+
+        // Consume the travel distance segment by segment so large steps don't skip corners
+        while !self.finished && remaining > 0.0 {
+            let (from, to) = self.segment_endpoints();
+            let length = (to - from).magnitude();
+
+            if length <= f32::EPSILON {
+                self.next_segment();
+                continue;
+            }
+
+            let distance_left = (1.0 - self.segment_progress) * length;
+            if remaining < distance_left {
+                self.segment_progress += remaining / length;
+                remaining = 0.0;
+            } else {
+                remaining -= distance_left;
+                self.next_segment();
+            }
+        }
+
+        Some(self.current_position())
+    }
+
+    // Length of one pass from the first waypoint to the last
+    fn path_length(&self) -> f32 {
+        self.config.waypoints.windows(2)
+            .map(|pair| (Vector3::from(pair[1]) - Vector3::from(pair[0])).magnitude())
+            .sum()
+    }
+
+    // Moves onto the next segment according to the path mode
+    fn next_segment(&mut self) {
+        let last_segment = self.config.waypoints.len() - 2;
+        self.segment_progress = 0.0;
+
+        if self.current_segment < last_segment {
+            self.current_segment += 1;
+            return;
+        }
+
+        match self.config.mode.as_str() {
+            "loop" => self.current_segment = 0,
+            "ping_pong" => {
+                self.current_segment = 0;
+                self.reversed = !self.reversed;
+            }
+            _ => {
+                // "once" or unrecognized mode, park on the final waypoint
+                self.segment_progress = 1.0;
+                self.finished = true;
+            }
+        }
+    }
+
+    // Returns the start and end of the current segment, taking the travel direction into account
+    fn segment_endpoints(&self) -> (Vector3<f32>, Vector3<f32>) {
+        let last_index = self.config.waypoints.len() - 1;
+        if self.reversed {
+            (self.waypoint(last_index - self.current_segment), self.waypoint(last_index - self.current_segment - 1))
+        } else {
+            (self.waypoint(self.current_segment), self.waypoint(self.current_segment + 1))
+        }
+    }
+
+    fn waypoint(&self, index: usize) -> Vector3<f32> {
+        let [x, y, z] = self.config.waypoints[index];
+        Vector3::new(x, y, z)
+    }
+
+    fn current_position(&self) -> Vector3<f32> {
+        if self.config.waypoints.len() == 1 {
+            return self.waypoint(0);
+        }
+        let (from, to) = self.segment_endpoints();
+        from + (to - from) * apply_easing(&self.config.easing, self.segment_progress)
+    }
+
+    /// Restarts the path from the first waypoint
+    pub fn reset(&mut self) {
+        self.current_segment = 0;
+        self.segment_progress = 0.0;
+        self.reversed = false;
+        self.finished = false;
+    }
+
+    /// Returns true once a "once" path has reached its final waypoint
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn get_config(&self) -> PathConfig {
+        self.config.clone()
+    }
+
+    pub fn set_speed(&mut self, speed: f32) {
+        self.config.speed = speed;
+    }
+
+    pub fn set_waypoints(&mut self, waypoints: Vec<[f32; 3]>) {
+        self.config.waypoints = waypoints;
+        self.reset();
+    }
+}
+
+// Maps linear progress (0.0 - 1.0) onto the requested easing curve
+fn apply_easing(easing: &str, t: f32) -> f32 {
+    match easing {
+        "ease_in" => t * t,
+        "ease_out" => t * (2.0 - t),
+        "ease_in_out" => {
+            if t < 0.5 {
+                2.0 * t * t
+            } else {
+                -1.0 + (4.0 - 2.0 * t) * t
+            }
+        }
+        _ => t, // "linear" or unrecognized easing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn follower(waypoints: Vec<[f32; 3]>, speed: f32, easing: &str, mode: &str) -> PathFollower {
+        PathFollower::new(PathConfig { waypoints, speed, easing: easing.to_string(), mode: mode.to_string() })
+    }
+
+    fn assert_at(position: Option<Vector3<f32>>, x: f32, y: f32) {
+        let position = position.expect("path has waypoints");
+        assert!((position.x - x).abs() < 1e-4 && (position.y - y).abs() < 1e-4, "expected ({}, {}), got {:?}", x, y, position);
+    }
+
+    #[test]
+    fn once_stops_on_the_last_waypoint() {
+        let mut path = follower(vec![[0.0, 0.0, 0.0], [10.0, 0.0, 0.0], [10.0, 10.0, 0.0]], 5.0, "linear", "once");
+        assert_at(path.advance(1.0), 5.0, 0.0);
+        assert_at(path.advance(2.0), 10.0, 5.0);
+        assert!(!path.is_finished());
+        assert_at(path.advance(10.0), 10.0, 10.0);
+        assert!(path.is_finished());
+        assert_at(path.advance(1.0), 10.0, 10.0);
+    }
+
+    #[test]
+    fn loop_starts_over_from_the_first_waypoint() {
+        let mut path = follower(vec![[0.0, 0.0, 0.0], [10.0, 0.0, 0.0]], 10.0, "linear", "loop");
+        assert_at(path.advance(1.5), 5.0, 0.0);
+        assert_at(path.advance(1000.25), 7.5, 0.0);
+        assert!(!path.is_finished());
+    }
+
+    #[test]
+    fn ping_pong_turns_around_at_the_ends() {
+        let mut path = follower(vec![[0.0, 0.0, 0.0], [10.0, 0.0, 0.0]], 10.0, "linear", "ping_pong");
+        assert_at(path.advance(1.5), 5.0, 0.0);
+        assert_at(path.advance(0.2), 3.0, 0.0);
+        assert_at(path.advance(0.5), 2.0, 0.0);
+        assert!(!path.is_finished());
+    }
+
+    #[test]
+    fn easing_shapes_progress_along_a_segment() {
+        let mut path = follower(vec![[0.0, 0.0, 0.0], [10.0, 0.0, 0.0]], 5.0, "ease_in", "once");
+        assert_at(path.advance(1.0), 2.5, 0.0);
+
+        let mut path = follower(vec![[0.0, 0.0, 0.0], [10.0, 0.0, 0.0]], 5.0, "ease_out", "once");
+        assert_at(path.advance(1.0), 7.5, 0.0);
+
+        let mut path = follower(vec![[0.0, 0.0, 0.0], [10.0, 0.0, 0.0]], 2.5, "ease_in_out", "once");
+        assert_at(path.advance(1.0), 1.25, 0.0);
+    }
+
+    #[test]
+    fn zero_length_path_returns_the_waypoint() {
+        for mode in ["once", "loop", "ping_pong"] {
+            let mut path = follower(vec![[1.0, 2.0, 0.0], [1.0, 2.0, 0.0]], 5.0, "linear", mode);
+            assert_at(path.advance(1.0), 1.0, 2.0);
+        }
+    }
+
+    #[test]
+    fn huge_step_on_a_tiny_loop_finishes() {
+        let mut path = follower(vec![[0.0, 0.0, 0.0], [0.001, 0.0, 0.0], [0.001, 0.001, 0.0]], 1.0e6, "linear", "loop");
+        let position = path.advance(100.0).unwrap();
+        assert!(position.x >= 0.0 && position.x <= 0.001 + 1e-6);
+    }
+
+    #[test]
+    fn empty_and_single_waypoint_paths() {
+        assert!(follower(Vec::new(), 5.0, "linear", "loop").advance(1.0).is_none());
+
+        let mut path = follower(vec![[3.0, 4.0, 0.0]], 5.0, "linear", "loop");
+        assert_at(path.advance(1.0), 3.0, 4.0);
+        assert!(path.is_finished());
+    }
+}
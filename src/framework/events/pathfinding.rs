@@ -0,0 +1,428 @@
+use std::{cmp::Ordering, collections::BinaryHeap};
+use nalgebra::Vector3;
+use crate::framework::graphics::{internal_object::graphics_object::Generic2DGraphicsObject, tilemap::Tilemap};
+
+const NEIGHBOURS: [(i32, i32); 8] = [(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+pub struct NavigationGrid {
+    width: usize,
+    height: usize,
+    cell_size: f32,
+    origin: Vector3<f32>, // World position of the bottom-left corner of cell (0, 0)
+    walkable: Vec<bool>,
+}
+
+// Entry in the A* open set, ordered so the BinaryHeap pops the lowest cost first
+struct OpenNode {
+    cost: f32,
+    index: usize,
+}
+
+impl PartialEq for OpenNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost == other.cost
+    }
+}
+
+impl Eq for OpenNode {}
+
+impl PartialOrd for OpenNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OpenNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+impl NavigationGrid {
+    /// Creates a grid where every cell is walkable
+    pub fn new(width: usize, height: usize, cell_size: f32, origin: Vector3<f32>) -> Self {
+        NavigationGrid {
+            width,
+            height,
+            cell_size,
+            origin,
+            walkable: vec![true; width * height],
+        }
+    }
+
+    /// Builds a grid from rows of walkability flags, the first row being the bottom of the grid
+    pub fn from_data(rows: &[Vec<bool>], cell_size: f32, origin: Vector3<f32>) -> Self {
+        let height = rows.len();
+        let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        let mut grid = Self::new(width, height, cell_size, origin);
+
+        for (y, row) in rows.iter().enumerate() {
+            for x in 0..width {
+                grid.set_walkable(x, y, row.get(x).copied().unwrap_or(false));
+            }
+        }
+        grid
+    }
+
+    /// Builds a grid with one cell per tile of a tilemap placed with its top-left corner at origin, as in Tilemap::add_to.
+    /// Tiles for which is_solid returns true are blocked and empty cells are walkable. For a separate collision layer
+    /// where any tile is solid, pass |_| true.
+    pub fn from_tilemap<F: Fn(usize) -> bool>(tilemap: &Tilemap, origin: Vector3<f32>, is_solid: F) -> Self {
+        let (width, height) = tilemap.get_size();
+        let tile_size = tilemap.get_tile_size();
+        let bottom_left = origin - Vector3::new(0.0, height as f32 * tile_size, 0.0);
+        let mut grid = Self::new(width, height, tile_size, bottom_left);
+
+        // Tilemap rows count down from the top, grid rows count up from the bottom
+        for tile_y in 0..height {
+            for x in 0..width {
+                if tilemap.get_tile(x, tile_y).is_some_and(&is_solid) {
+                    grid.set_walkable(x, height - 1 - tile_y, false);
+                }
+            }
+        }
+        grid
+    }
+
+    pub fn set_walkable(&mut self, x: usize, y: usize, walkable: bool) {
+        if x < self.width && y < self.height {
+            self.walkable[y * self.width + x] = walkable;
+        }
+    }
+
+    pub fn is_walkable(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.walkable[y * self.width + x]
+    }
+
+    /// Marks every cell overlapping the given world-space rectangle as blocked
+    pub fn block_area(&mut self, min: Vector3<f32>, max: Vector3<f32>) {
+        let (min_x, min_y) = self.world_to_cell_clamped(min);
+        let (max_x, max_y) = self.world_to_cell_clamped(max);
+
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                self.set_walkable(x, y, false);
+            }
+        }
+    }
+
+    /// Marks the cells covered by an object's bounds as blocked
+    pub fn block_object(&mut self, object: &Generic2DGraphicsObject) {
+        let position = object.get_position();
+        let (width, height) = object.dimensions();
+        let half_extents = Vector3::new(width / 2.0, height / 2.0, 0.0);
+        self.block_area(position - half_extents, position + half_extents);
+    }
+
+    /// Converts a world position into cell coordinates, or None if it lies outside the grid
+    pub fn world_to_cell(&self, position: Vector3<f32>) -> Option<(usize, usize)> {
+        let x = ((position.x - self.origin.x) / self.cell_size).floor();
+        let y = ((position.y - self.origin.y) / self.cell_size).floor();
+
+        if x < 0.0 || y < 0.0 || x >= self.width as f32 || y >= self.height as f32 {
+            return None;
+        }
+        Some((x as usize, y as usize))
+    }
+
+    fn world_to_cell_clamped(&self, position: Vector3<f32>) -> (usize, usize) {
+        let x = ((position.x - self.origin.x) / self.cell_size).floor();
+        let y = ((position.y - self.origin.y) / self.cell_size).floor();
+        (
+            x.clamp(0.0, self.width.saturating_sub(1) as f32) as usize,
+            y.clamp(0.0, self.height.saturating_sub(1) as f32) as usize,
+        )
+    }
+
+    /// Returns the world position of the center of a cell
+    pub fn cell_to_world(&self, x: usize, y: usize) -> Vector3<f32> {
+        Vector3::new(
+            self.origin.x + (x as f32 + 0.5) * self.cell_size,
+            self.origin.y + (y as f32 + 0.5) * self.cell_size,
+            self.origin.z,
+        )
+    }
+
+    /// Finds a path between two world positions, returned as cell centers from start to goal
+    pub fn find_path(&self, start: Vector3<f32>, goal: Vector3<f32>, smooth: bool) -> Option<Vec<Vector3<f32>>> {
+        let start_cell = self.world_to_cell(start)?;
+        let goal_cell = self.world_to_cell(goal)?;
+        let mut cells = self.find_path_cells(start_cell, goal_cell)?;
+
+        if smooth {
+            cells = self.smooth_path(&cells);
+        }
+
+        Some(cells.into_iter().map(|(x, y)| self.cell_to_world(x, y)).collect())
+    }
+
+    /// Runs A* between two cells, moving in 8 directions without cutting blocked corners
+    pub fn find_path_cells(&self, start: (usize, usize), goal: (usize, usize)) -> Option<Vec<(usize, usize)>> {
+        if !self.is_walkable(start.0, start.1) || !self.is_walkable(goal.0, goal.1) {
+            return None;
+        }
+
+        let cell_count = self.width * self.height;
+        let start_index = start.1 * self.width + start.0;
+        let goal_index = goal.1 * self.width + goal.0;
+
+        let mut cost_so_far = vec![f32::INFINITY; cell_count];
+        let mut came_from: Vec<Option<usize>> = vec![None; cell_count];
+        let mut closed = vec![false; cell_count];
+        let mut open = BinaryHeap::new();
+
+        cost_so_far[start_index] = 0.0;
+        open.push(OpenNode { cost: Self::heuristic(start, goal), index: start_index });
+
+        while let Some(OpenNode { index, .. }) = open.pop() {
+            if index == goal_index {
+                return Some(self.reconstruct_path(&came_from, goal_index));
+            }
+            if closed[index] {
+                continue;
+            }
+            closed[index] = true;
+
+            let (x, y) = (index % self.width, index / self.width);
+            for (dx, dy) in NEIGHBOURS {
+                let nx = x as i32 + dx;
+                let ny = y as i32 + dy;
+                if nx < 0 || ny < 0 || !self.is_walkable(nx as usize, ny as usize) {
+                    continue;
+                }
+
+                // Diagonal moves need both adjacent cells free so paths don't clip wall corners
+                if dx != 0 && dy != 0 && (!self.is_walkable(nx as usize, y) || !self.is_walkable(x, ny as usize)) {
+                    continue;
+                }
+
+                let neighbour = (nx as usize, ny as usize);
+                let neighbour_index = neighbour.1 * self.width + neighbour.0;
+                let step_cost = if dx != 0 && dy != 0 { std::f32::consts::SQRT_2 } else { 1.0 };
+                let new_cost = cost_so_far[index] + step_cost;
+
+                if new_cost < cost_so_far[neighbour_index] {
+                    cost_so_far[neighbour_index] = new_cost;
+                    came_from[neighbour_index] = Some(index);
+                    open.push(OpenNode { cost: new_cost + Self::heuristic(neighbour, goal), index: neighbour_index });
+                }
+            }
+        }
+
+        None
+    }
+
+    fn reconstruct_path(&self, came_from: &[Option<usize>], goal_index: usize) -> Vec<(usize, usize)> {
+        let mut path = vec![(goal_index % self.width, goal_index / self.width)];
+        let mut current = goal_index;
+        while let Some(previous) = came_from[current] {
+            path.push((previous % self.width, previous / self.width));
+            current = previous;
+        }
+        path.reverse();
+        path
+    }
+
+    // Octile distance, admissible for 8-directional movement
+    fn heuristic(from: (usize, usize), to: (usize, usize)) -> f32 {
+        let dx = (from.0 as f32 - to.0 as f32).abs();
+        let dy = (from.1 as f32 - to.1 as f32).abs();
+        dx.max(dy) + (std::f32::consts::SQRT_2 - 1.0) * dx.min(dy)
+    }
+
+    /// Removes intermediate cells that have a clear line of sight to each other
+    pub fn smooth_path(&self, path: &[(usize, usize)]) -> Vec<(usize, usize)> {
+        if path.len() < 3 {
+            return path.to_vec();
+        }
+
+        let mut smoothed = vec![path[0]];
+        let mut anchor = 0;
+        for i in 2..path.len() {
+            if !self.has_line_of_sight(path[anchor], path[i]) {
+                anchor = i - 1;
+                smoothed.push(path[anchor]);
+            }
+        }
+        smoothed.push(path[path.len() - 1]);
+        smoothed
+    }
+
+    /// Walks the cells between two points (Bresenham) and checks that all of them are walkable
+    pub fn has_line_of_sight(&self, from: (usize, usize), to: (usize, usize)) -> bool {
+        let (mut x, mut y) = (from.0 as i32, from.1 as i32);
+        let (end_x, end_y) = (to.0 as i32, to.1 as i32);
+        let dx = (end_x - x).abs();
+        let dy = -(end_y - y).abs();
+        let step_x = if x < end_x { 1 } else { -1 };
+        let step_y = if y < end_y { 1 } else { -1 };
+        let mut error = dx + dy;
+
+        loop {
+            if !self.is_walkable(x as usize, y as usize) {
+                return false;
+            }
+            if x == end_x && y == end_y {
+                return true;
+            }
+            let doubled = 2 * error;
+            let moves_x = doubled >= dy;
+            let moves_y = doubled <= dx;
+
+            // A diagonal step must not squeeze between two blocked cells
+            if moves_x && moves_y && (!self.is_walkable((x + step_x) as usize, y as usize) || !self.is_walkable(x as usize, (y + step_y) as usize)) {
+                return false;
+            }
+            if moves_x {
+                error += dy;
+                x += step_x;
+            }
+            if moves_y {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get_cell_size(&self) -> f32 {
+        self.cell_size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::graphics::util::master_graphics_list::MasterGraphicsList;
+
+    // Rows are given top to bottom so the layout reads like the map, '#' is blocked
+    fn grid_from(rows: &[&str]) -> NavigationGrid {
+        let data: Vec<Vec<bool>> = rows.iter().rev().map(|row| row.chars().map(|c| c != '#').collect()).collect();
+        NavigationGrid::from_data(&data, 1.0, Vector3::zeros())
+    }
+
+    fn is_connected(path: &[(usize, usize)]) -> bool {
+        path.windows(2).all(|pair| {
+            let dx = (pair[0].0 as i32 - pair[1].0 as i32).abs();
+            let dy = (pair[0].1 as i32 - pair[1].1 as i32).abs();
+            dx <= 1 && dy <= 1 && (dx, dy) != (0, 0)
+        })
+    }
+
+    #[test]
+    fn finds_the_straight_path_in_the_open() {
+        let grid = grid_from(&[".....", ".....", "....."]);
+        let path = grid.find_path_cells((0, 1), (4, 1)).unwrap();
+        assert_eq!(path, vec![(0, 1), (1, 1), (2, 1), (3, 1), (4, 1)]);
+    }
+
+    #[test]
+    fn goes_around_a_wall() {
+        let grid = grid_from(&[
+            ".....",
+            "..#..",
+            "..#..",
+            "..#..",
+        ]);
+        let path = grid.find_path_cells((0, 0), (4, 0)).unwrap();
+        assert_eq!(path.first(), Some(&(0, 0)));
+        assert_eq!(path.last(), Some(&(4, 0)));
+        assert!(is_connected(&path));
+        assert!(path.iter().all(|&(x, y)| grid.is_walkable(x, y)));
+        assert!(path.contains(&(2, 3)), "the only gap is over the top of the wall");
+    }
+
+    #[test]
+    fn does_not_cut_blocked_corners() {
+        let grid = grid_from(&[
+            ".#",
+            "#.",
+        ]);
+        assert_eq!(grid.find_path_cells((0, 1), (1, 0)), None);
+    }
+
+    #[test]
+    fn unreachable_and_blocked_endpoints_have_no_path() {
+        let grid = grid_from(&[
+            "..#..",
+            "..#..",
+            "..#..",
+        ]);
+        assert_eq!(grid.find_path_cells((0, 0), (4, 0)), None);
+        assert_eq!(grid.find_path_cells((2, 0), (0, 0)), None);
+        assert_eq!(grid.find_path_cells((0, 0), (2, 1)), None);
+        assert_eq!(grid.find_path(Vector3::new(0.5, 0.5, 0.0), Vector3::new(50.0, 0.5, 0.0), false), None);
+    }
+
+    #[test]
+    fn smoothing_keeps_only_the_corners() {
+        let grid = grid_from(&[
+            ".....",
+            ".....",
+            "###..",
+            ".....",
+        ]);
+        let path = grid.find_path_cells((0, 0), (0, 3)).unwrap();
+        let smoothed = grid.smooth_path(&path);
+        assert!(smoothed.len() < path.len());
+        assert_eq!(smoothed.first(), Some(&(0, 0)));
+        assert_eq!(smoothed.last(), Some(&(0, 3)));
+        for pair in smoothed.windows(2) {
+            assert!(grid.has_line_of_sight(pair[0], pair[1]));
+        }
+
+        let open = grid.find_path_cells((0, 3), (4, 2)).unwrap();
+        assert_eq!(grid.smooth_path(&open), vec![(0, 3), (4, 2)]);
+    }
+
+    #[test]
+    fn line_of_sight_is_blocked_by_walls_and_diagonal_gaps() {
+        let grid = grid_from(&[
+            "...",
+            ".#.",
+            "...",
+        ]);
+        assert!(!grid.has_line_of_sight((0, 1), (2, 1)));
+        assert!(grid.has_line_of_sight((0, 0), (2, 0)));
+
+        let grid = grid_from(&[
+            ".#",
+            "#.",
+        ]);
+        assert!(!grid.has_line_of_sight((0, 1), (1, 0)));
+    }
+
+    #[test]
+    fn world_paths_use_cell_centers() {
+        let grid = grid_from(&["..."]);
+        let path = grid.find_path(Vector3::new(0.2, 0.7, 0.0), Vector3::new(2.9, 0.1, 0.0), true).unwrap();
+        assert_eq!(path, vec![Vector3::new(0.5, 0.5, 0.0), Vector3::new(2.5, 0.5, 0.0)]);
+    }
+
+    #[test]
+    fn tilemap_solid_tiles_become_blocked_cells() {
+        let mut tilemap = Tilemap::new("level", 3, 2, 16.0, 4, 4, 16);
+        tilemap.set_tiles(vec![Some(1), None, Some(2), None, None, Some(1)], &MasterGraphicsList::new()).unwrap();
+
+        let grid = NavigationGrid::from_tilemap(&tilemap, Vector3::new(100.0, 200.0, 0.0), |tile| tile == 1);
+        assert_eq!((grid.get_width(), grid.get_height(), grid.get_cell_size()), (3, 2, 16.0));
+
+        // The tilemap's top row is the grid's row 1
+        assert!(!grid.is_walkable(0, 1));
+        assert!(grid.is_walkable(1, 1));
+        assert!(grid.is_walkable(2, 1));
+        assert!(grid.is_walkable(0, 0));
+        assert!(!grid.is_walkable(2, 0));
+
+        // The top-left tile covers x 100..116 and y 184..200 in the world
+        assert_eq!(grid.world_to_cell(Vector3::new(101.0, 199.0, 0.0)), Some((0, 1)));
+        assert_eq!(grid.world_to_cell(Vector3::new(140.0, 170.0, 0.0)), Some((2, 0)));
+    }
+}
@@ -0,0 +1,140 @@
+use nalgebra::Vector3;
+use crate::framework::graphics::{internal_object::animation_config::AnimationConfig, util::master_graphics_list::MasterGraphicsList};
+
+pub enum SequenceAction {
+    Wait(f32), // Seconds
+    MoveTo { object: String, target: Vector3<f32>, speed: f32 }, // Finishes when the object reaches the target
+    SetAnimation { object: String, animation_config: Option<AnimationConfig> },
+    Call(Box<dyn FnMut(&MasterGraphicsList) + Send>), // Runs once, for firing game events
+}
+
+/// A list of actions run one after another across frames, for cutscenes and scripted patterns
+pub struct Sequence {
+    actions: Vec<SequenceAction>,
+    current_action: usize,
+    action_elapsed: f32,
+    looping: bool,
+}
+
+impl Sequence {
+    pub fn new() -> Self {
+        Sequence {
+            actions: Vec::new(),
+            current_action: 0,
+            action_elapsed: 0.0,
+            looping: false,
+        }
+    }
+
+    pub fn wait(mut self, seconds: f32) -> Self {
+        self.actions.push(SequenceAction::Wait(seconds));
+        self
+    }
+
+    pub fn move_to(mut self, object: &str, target: Vector3<f32>, speed: f32) -> Self {
+        self.actions.push(SequenceAction::MoveTo { object: object.to_owned(), target, speed });
+        self
+    }
+
+    pub fn set_animation(mut self, object: &str, animation_config: Option<AnimationConfig>) -> Self {
+        self.actions.push(SequenceAction::SetAnimation { object: object.to_owned(), animation_config });
+        self
+    }
+
+    pub fn call<F: FnMut(&MasterGraphicsList) + Send + 'static>(mut self, callback: F) -> Self {
+        self.actions.push(SequenceAction::Call(Box::new(callback)));
+        self
+    }
+
+    /// Restart from the first action after the last one finishes
+    pub fn looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    /// Runs the sequence for one frame. Instant actions chain within the same frame. Returns true once the sequence has finished.
+    pub fn update(&mut self, delta_time: f32, graphics_list: &MasterGraphicsList) -> bool {
+        let mut remaining_time = delta_time;
+
+        // Bounded so a looping sequence made only of instant actions can't spin forever
+        for _ in 0..=self.actions.len() {
+            if self.current_action >= self.actions.len() {
+                if !self.looping || self.actions.is_empty() {
+                    return true;
+                }
+                self.current_action = 0;
+            }
+
+            let (done, leftover) = self.run_action(remaining_time, graphics_list);
+            if !done {
+                return false;
+            }
+            remaining_time = leftover;
+            self.current_action += 1;
+            self.action_elapsed = 0.0;
+        }
+
+        self.is_finished()
+    }
+
+    // Returns whether the current action finished, and how much of delta_time it didn't use
+    fn run_action(&mut self, delta_time: f32, graphics_list: &MasterGraphicsList) -> (bool, f32) {
+        match &mut self.actions[self.current_action] {
+            SequenceAction::Wait(seconds) => {
+                self.action_elapsed += delta_time;
+                if self.action_elapsed >= *seconds {
+                    (true, self.action_elapsed - *seconds)
+                } else {
+                    (false, 0.0)
+                }
+            }
+            SequenceAction::MoveTo { object, target, speed } => {
+                let Some(object) = graphics_list.get_object(object) else {
+                    log::warn!("Sequence could not find object '{}'", object);
+                    return (true, delta_time);
+                };
+                let mut object = object.write().unwrap();
+                let position = object.get_position();
+                let offset = *target - position;
+                let distance = offset.magnitude();
+                let step = *speed * delta_time;
+
+                if step >= distance {
+                    object.set_position(*target);
+                    let leftover = if *speed > 0.0 { (step - distance) / *speed } else { 0.0 };
+                    (true, leftover)
+                } else {
+                    object.set_position(position + offset / distance * step);
+                    (false, 0.0)
+                }
+            }
+            SequenceAction::SetAnimation { object, animation_config } => {
+                match graphics_list.get_object(object) {
+                    Some(object) => object.write().unwrap().set_animation_config(animation_config.clone()),
+                    None => log::warn!("Sequence could not find object '{}'", object),
+                }
+                (true, delta_time)
+            }
+            SequenceAction::Call(callback) => {
+                callback(graphics_list);
+                (true, delta_time)
+            }
+        }
+    }
+
+    /// Starts the sequence over from the first action
+    pub fn reset(&mut self) {
+        self.current_action = 0;
+        self.action_elapsed = 0.0;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        !self.looping && self.current_action >= self.actions.len()
+    }
+}
+
+impl Default for Sequence {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+/// What a state wants to happen after an update
+pub enum Transition {
+    None,
+    Switch(String), // Change to the named state. Bubbles up to the parent machine if this machine doesn't have it.
+}
+
+pub trait State<C> {
+    fn enter(&mut self, _context: &mut C) {}
+    fn update(&mut self, context: &mut C, delta_time: f32) -> Transition;
+    fn exit(&mut self, _context: &mut C) {}
+}
+
+/// A machine of named states over some context C (game flow, an entity's AI...).
+/// A StateMachine is itself a State, so a state can be a whole nested machine.
+pub struct StateMachine<C> {
+    states: HashMap<String, Box<dyn State<C> + Send + Sync>>,
+    initial_state: Option<String>,
+    current_state: Option<String>,
+}
+
+impl<C> StateMachine<C> {
+    pub fn new() -> Self {
+        StateMachine {
+            states: HashMap::new(),
+            initial_state: None,
+            current_state: None,
+        }
+    }
+
+    /// Adds a state. The first state added is where the machine starts unless set_initial_state says otherwise.
+    pub fn add_state<S: State<C> + Send + Sync + 'static>(&mut self, name: &str, state: S) {
+        if self.initial_state.is_none() {
+            self.initial_state = Some(name.to_owned());
+        }
+        self.states.insert(name.to_owned(), Box::new(state));
+    }
+
+    pub fn set_initial_state(&mut self, name: &str) {
+        self.initial_state = Some(name.to_owned());
+    }
+
+    pub fn has_state(&self, name: &str) -> bool {
+        self.states.contains_key(name)
+    }
+
+    /// Enters the initial state
+    pub fn start(&mut self, context: &mut C) {
+        if let Some(initial_state) = self.initial_state.clone() {
+            self.enter_state(&initial_state, context);
+        }
+    }
+
+    /// Exits the current state and enters the named one. Returns false if this machine has no such state.
+    pub fn transition_to(&mut self, name: &str, context: &mut C) -> bool {
+        if !self.states.contains_key(name) {
+            return false;
+        }
+        self.exit_current(context);
+        self.enter_state(name, context);
+        true
+    }
+
+    fn enter_state(&mut self, name: &str, context: &mut C) {
+        if let Some(state) = self.states.get_mut(name) {
+            state.enter(context);
+            self.current_state = Some(name.to_owned());
+        }
+    }
+
+    fn exit_current(&mut self, context: &mut C) {
+        if let Some(current_state) = self.current_state.take() {
+            if let Some(state) = self.states.get_mut(&current_state) {
+                state.exit(context);
+            }
+        }
+    }
+
+    /// Updates the current state and applies the transition it asks for.
+    /// Returns any transition this machine couldn't resolve so a parent machine can handle it.
+    pub fn update_machine(&mut self, context: &mut C, delta_time: f32) -> Transition {
+        let transition = match self.current_state.as_ref().and_then(|name| self.states.get_mut(name)) {
+            Some(state) => state.update(context, delta_time),
+            None => return Transition::None,
+        };
+
+        match transition {
+            Transition::Switch(name) if self.has_state(&name) => {
+                self.transition_to(&name, context);
+                Transition::None
+            }
+            other => other,
+        }
+    }
+
+    /// Name of the active state in this machine (not descending into nested machines)
+    pub fn get_current_state(&self) -> Option<&str> {
+        self.current_state.as_deref()
+    }
+}
+
+impl<C> Default for StateMachine<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C> State<C> for StateMachine<C> {
+    fn enter(&mut self, context: &mut C) {
+        self.start(context);
+    }
+
+    fn update(&mut self, context: &mut C, delta_time: f32) -> Transition {
+        self.update_machine(context, delta_time)
+    }
+
+    fn exit(&mut self, context: &mut C) {
+        self.exit_current(context);
+    }
+}
@@ -0,0 +1,123 @@
+use nalgebra::Vector3;
+
+// Steering helpers return the velocity an agent would like to have. Feed the result into
+// apply_steering (or straight into move_object) to actually move the agent.
+
+/// Head straight for the target at full speed
+pub fn seek(position: Vector3<f32>, target: Vector3<f32>, max_speed: f32) -> Vector3<f32> {
+    direction_to(position, target) * max_speed
+}
+
+/// Run directly away from the threat at full speed, only while it is within panic_distance (0.0 = always)
+pub fn flee(position: Vector3<f32>, threat: Vector3<f32>, max_speed: f32, panic_distance: f32) -> Vector3<f32> {
+    if panic_distance > 0.0 && planar(threat - position).magnitude() > panic_distance {
+        return Vector3::zeros();
+    }
+    direction_to(threat, position) * max_speed
+}
+
+/// Like seek, but slows down linearly once inside slowing_radius so the agent stops on the target
+pub fn arrive(position: Vector3<f32>, target: Vector3<f32>, max_speed: f32, slowing_radius: f32) -> Vector3<f32> {
+    let offset = planar(target - position);
+    let distance = offset.magnitude();
+    if distance <= f32::EPSILON {
+        return Vector3::zeros();
+    }
+
+    let speed = if slowing_radius > 0.0 && distance < slowing_radius {
+        max_speed * (distance / slowing_radius)
+    } else {
+        max_speed
+    };
+    offset / distance * speed
+}
+
+/// Push away from neighbours closer than radius, weighted so nearer neighbours push harder
+pub fn separation(position: Vector3<f32>, neighbours: &[Vector3<f32>], radius: f32, max_speed: f32) -> Vector3<f32> {
+    let mut push = Vector3::zeros();
+
+    for neighbour in neighbours {
+        let offset = planar(position - neighbour);
+        let distance = offset.magnitude();
+        // Skip ourselves (zero distance) and anything out of range
+        if distance > f32::EPSILON && distance < radius {
+            push += offset / distance * (1.0 - distance / radius);
+        }
+    }
+
+    if push.magnitude() <= f32::EPSILON {
+        return Vector3::zeros();
+    }
+    push.normalize() * max_speed
+}
+
+/// Blends a desired velocity into the current one, limiting how quickly the agent can turn or accelerate
+pub fn apply_steering(current_velocity: Vector3<f32>, desired_velocity: Vector3<f32>, max_force: f32, max_speed: f32) -> Vector3<f32> {
+    let steering = truncate(desired_velocity - current_velocity, max_force);
+    truncate(current_velocity + steering, max_speed)
+}
+
+/// Clamps a vector's length without changing its direction
+pub fn truncate(vector: Vector3<f32>, max_length: f32) -> Vector3<f32> {
+    let length = vector.magnitude();
+    if length > max_length && length > 0.0 {
+        vector * (max_length / length)
+    } else {
+        vector
+    }
+}
+
+// The engine is 2D, so steering ignores the Z (depth) axis
+fn planar(vector: Vector3<f32>) -> Vector3<f32> {
+    Vector3::new(vector.x, vector.y, 0.0)
+}
+
+fn direction_to(from: Vector3<f32>, to: Vector3<f32>) -> Vector3<f32> {
+    let offset = planar(to - from);
+    if offset.magnitude() <= f32::EPSILON {
+        return Vector3::zeros();
+    }
+    offset.normalize()
+}
+
+pub struct Wander {
+    circle_distance: f32, // How far ahead of the agent the wander circle sits
+    circle_radius: f32,
+    jitter: f32, // Maximum change in wander angle per second (radians)
+    angle: f32,
+}
+
+impl Wander {
+    pub fn new(circle_distance: f32, circle_radius: f32, jitter: f32) -> Self {
+        Wander {
+            circle_distance,
+            circle_radius,
+            jitter,
+            angle: 0.0,
+        }
+    }
+
+    /// Produces a meandering velocity by nudging a target around a circle projected ahead of the agent
+    pub fn update(&mut self, current_velocity: Vector3<f32>, max_speed: f32, delta_time: f32) -> Vector3<f32> {
+        use rand::Rng;
+        let mut rng = rand::rng();
+        let max_change = self.jitter * delta_time;
+        if max_change > 0.0 {
+            self.angle += rng.random_range(-max_change..=max_change);
+        }
+
+        let heading = if planar(current_velocity).magnitude() > f32::EPSILON {
+            planar(current_velocity).normalize()
+        } else {
+            Vector3::x()
+        };
+
+        let circle_center = heading * self.circle_distance;
+        let displacement = Vector3::new(self.angle.cos(), self.angle.sin(), 0.0) * self.circle_radius;
+        direction_to(Vector3::zeros(), circle_center + displacement) * max_speed
+    }
+
+    pub fn set_jitter(&mut self, jitter: f32) {
+        self.jitter = jitter;
+    }
+}
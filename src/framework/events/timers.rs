@@ -0,0 +1,96 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerHandle(u64);
+
+struct Timer {
+    handle: TimerHandle,
+    remaining: f32,
+    repeat_interval: Option<f32>, // Some for timers created with every()
+    callback: Box<dyn FnMut() + Send>,
+}
+
+pub struct Timers {
+    timers: Vec<Timer>,
+    next_handle: u64,
+}
+
+impl Timers {
+    pub fn new() -> Self {
+        Timers {
+            timers: Vec::new(),
+            next_handle: 0,
+        }
+    }
+
+    /// Runs the callback once after the given number of seconds
+    pub fn after<F: FnMut() + Send + 'static>(&mut self, seconds: f32, callback: F) -> TimerHandle {
+        self.schedule(seconds, None, Box::new(callback))
+    }
+
+    /// Runs the callback every interval seconds until cancelled
+    pub fn every<F: FnMut() + Send + 'static>(&mut self, interval: f32, callback: F) -> TimerHandle {
+        self.schedule(interval, Some(interval), Box::new(callback))
+    }
+
+    fn schedule(&mut self, seconds: f32, repeat_interval: Option<f32>, callback: Box<dyn FnMut() + Send>) -> TimerHandle {
+        let handle = TimerHandle(self.next_handle);
+        self.next_handle += 1;
+        self.timers.push(Timer {
+            handle,
+            remaining: seconds,
+            repeat_interval,
+            callback,
+        });
+        handle
+    }
+
+    /// Stops a timer before it fires. Returns false if it had already finished or been cancelled.
+    pub fn cancel(&mut self, handle: TimerHandle) -> bool {
+        let count = self.timers.len();
+        self.timers.retain(|timer| timer.handle != handle);
+        self.timers.len() != count
+    }
+
+    /// Advances every timer by delta_time and runs the callbacks that come due. Call once per frame.
+    pub fn update(&mut self, delta_time: f32) {
+        for timer in self.timers.iter_mut() {
+            timer.remaining -= delta_time;
+
+            match timer.repeat_interval {
+                Some(interval) if interval > 0.0 => {
+                    // A long frame may owe a repeating timer several calls
+                    while timer.remaining <= 0.0 {
+                        (timer.callback)();
+                        timer.remaining += interval;
+                    }
+                }
+                _ => {
+                    if timer.remaining <= 0.0 {
+                        (timer.callback)();
+                    }
+                }
+            }
+        }
+
+        self.timers.retain(|timer| timer.repeat_interval.is_some_and(|interval| interval > 0.0) || timer.remaining > 0.0);
+    }
+
+    pub fn is_pending(&self, handle: TimerHandle) -> bool {
+        self.timers.iter().any(|timer| timer.handle == handle)
+    }
+
+    /// Seconds until the timer next fires, or None if it is no longer pending
+    pub fn get_remaining(&self, handle: TimerHandle) -> Option<f32> {
+        self.timers.iter().find(|timer| timer.handle == handle).map(|timer| timer.remaining)
+    }
+
+    /// Cancels every timer
+    pub fn clear(&mut self) {
+        self.timers.clear();
+    }
+}
+
+impl Default for Timers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,182 @@
+use glfw::Context;
+
+use super::{display::{DisplayManager, DisplayMode}, error::{EngineError, EngineResult}, framework_controller::{FrameworkController, ScalingPolicy}, graphics::{gl_check, texture_manager::TextureSettings}, settings::Settings};
+
+/// The GLFW handle, window and event queue created by FrameworkBuilder::build
+pub struct WindowContext {
+    pub glfw: glfw::Glfw,
+    pub window: glfw::PWindow,
+    pub events: glfw::GlfwReceiver<(f64, glfw::WindowEvent)>,
+    pub display_manager: DisplayManager, // Already in the builder's display mode
+}
+
+impl Drop for WindowContext {
+    /// The window takes the GL context with it, so GL resources that outlive it (e.g. a controller declared
+    /// after the context) must not try to delete themselves
+    fn drop(&mut self) {
+        gl_check::set_context_alive(false);
+    }
+}
+
+/// Configures and creates the window, GL context and FrameworkController in one go
+pub struct FrameworkBuilder {
+    width: u32,
+    height: u32,
+    title: String,
+    resizable: bool,
+    vsync: bool,
+    gl_version: (u32, u32),
+    gl_es: bool,
+    scaling_policy: ScalingPolicy,
+    samples: u32, // MSAA samples per pixel, 0 for none
+    texture_settings: TextureSettings, // Default filtering for the controller's TextureManager
+    srgb: bool,
+    display_mode: DisplayMode, // Applied on the primary monitor once the window exists
+}
+
+impl Default for FrameworkBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameworkBuilder {
+    pub fn new() -> Self {
+        FrameworkBuilder {
+            // There is something holy about this resolution
+            width: 640,
+            height: 480,
+            title: "rusted_open".to_string(),
+            resizable: true,
+            vsync: true,
+            gl_version: (3, 3),
+            gl_es: false,
+            scaling_policy: ScalingPolicy::default(),
+            samples: 0,
+            texture_settings: TextureSettings::default(),
+            srgb: false,
+            display_mode: DisplayMode::Windowed,
+        }
+    }
+
+    /// A builder with the window size, fullscreen and vsync from loaded settings.
+    /// The rest of the settings aren't the window's: pass asset_root to AssetManager::new and read volumes and keybindings from them directly.
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self::new()
+            .size(settings.window_width, settings.window_height)
+            .display_mode(if settings.fullscreen { DisplayMode::Fullscreen } else { DisplayMode::Windowed })
+            .vsync(settings.vsync)
+    }
+
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = title.to_owned();
+        self
+    }
+
+    pub fn resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+
+    pub fn vsync(mut self, vsync: bool) -> Self {
+        self.vsync = vsync;
+        self
+    }
+
+    /// The OpenGL core profile version to request
+    pub fn gl_version(mut self, major: u32, minor: u32) -> Self {
+        self.gl_version = (major, minor);
+        self
+    }
+
+    /// Requests an OpenGL ES 3.0 context instead of desktop GL, e.g. for a Raspberry Pi.
+    /// Shaders keep their desktop #version line, it is rewritten to GLSL ES 3.00 at compile time.
+    pub fn gl_es(mut self, gl_es: bool) -> Self {
+        self.gl_es = gl_es;
+        if gl_es {
+            self.gl_version = (3, 0);
+        }
+        self
+    }
+
+    /// How the scene adapts when the window is resized away from the configured size
+    pub fn scaling_policy(mut self, scaling_policy: ScalingPolicy) -> Self {
+        self.scaling_policy = scaling_policy;
+        self
+    }
+
+    /// Requests a multisampled framebuffer with this many samples per pixel (e.g. 4) to smooth polygon edges.
+    /// Leave at 0 for pixel art. Frames that go through post effects are rendered offscreen without multisampling.
+    pub fn samples(mut self, samples: u32) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    /// Default filtering and mipmapping for textures loaded through the controller's TextureManager
+    pub fn texture_settings(mut self, texture_settings: TextureSettings) -> Self {
+        self.texture_settings = texture_settings;
+        self
+    }
+
+    /// Requests an sRGB-capable framebuffer and turns on gamma-correct rendering, see FrameworkController::set_srgb
+    pub fn srgb(mut self, srgb: bool) -> Self {
+        self.srgb = srgb;
+        self
+    }
+
+    /// Starts the window borderless or fullscreen on the primary monitor instead of windowed.
+    /// Fullscreen keeps the monitor's current video mode, the window size is what switching back to windowed restores.
+    pub fn display_mode(mut self, display_mode: DisplayMode) -> Self {
+        self.display_mode = display_mode;
+        self
+    }
+
+    /// Creates the window, makes its context current and sets up the FrameworkController for it
+    pub fn build(self) -> EngineResult<(FrameworkController, WindowContext)> {
+        let mut glfw = glfw::init(glfw::log_errors).map_err(|e| EngineError::WindowCreation(format!("Failed to initialize GLFW: {:?}", e)))?;
+
+        glfw.window_hint(glfw::WindowHint::ContextVersion(self.gl_version.0, self.gl_version.1));
+        if self.gl_es {
+            glfw.window_hint(glfw::WindowHint::ClientApi(glfw::ClientApiHint::OpenGlEs));
+        } else {
+            glfw.window_hint(glfw::WindowHint::OpenGlProfile(glfw::OpenGlProfileHint::Core));
+            glfw.window_hint(glfw::WindowHint::OpenGlForwardCompat(true));
+        }
+        glfw.window_hint(glfw::WindowHint::Resizable(self.resizable));
+        glfw.window_hint(glfw::WindowHint::Samples(Some(self.samples)));
+        glfw.window_hint(glfw::WindowHint::SRgbCapable(self.srgb));
+
+        let (mut window, events) = glfw
+            .create_window(self.width, self.height, &self.title, glfw::WindowMode::Windowed)
+            .ok_or_else(|| EngineError::WindowCreation(format!("Failed to create a {}x{} window with OpenGL{} {}.{}", self.width, self.height, if self.gl_es { " ES" } else { "" }, self.gl_version.0, self.gl_version.1)))?;
+
+        window.make_current();
+        window.set_all_polling(true);
+        glfw.set_swap_interval(if self.vsync { glfw::SwapInterval::Sync(1) } else { glfw::SwapInterval::None });
+
+        // The design resolution is the configured window size, even when starting fullscreen
+        let (design_width, design_height) = window.get_framebuffer_size();
+        let mut display_manager = DisplayManager::new(&window);
+        if self.display_mode != DisplayMode::Windowed {
+            display_manager.set_display_mode(&mut glfw, &mut window, self.display_mode, 0, None)?;
+        }
+
+        let mut framework_controller = FrameworkController::new()?;
+        let (framebuffer_width, framebuffer_height) = window.get_framebuffer_size();
+        framework_controller.set_design_resolution(design_width as f32, design_height as f32);
+        framework_controller.set_scaling_policy(self.scaling_policy);
+        framework_controller.set_resolution(framebuffer_width as f32, framebuffer_height as f32);
+        framework_controller.get_texture_manager().read().unwrap().set_default_settings(self.texture_settings);
+        if self.srgb {
+            framework_controller.set_srgb(true);
+        }
+
+        Ok((framework_controller, WindowContext { glfw, window, events, display_manager }))
+    }
+}
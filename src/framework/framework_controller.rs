@@ -1,23 +1,62 @@
-use std::sync::{Arc, RwLock};
+use std::{sync::{Arc, RwLock}, time::Instant};
 
+use gl::types::GLuint;
 use glfw::Context;
 use nalgebra::{Matrix4, Vector3};
 
-use crate::framework::graphics;
+use crate::framework::{error::{EngineError, EngineResult}, events::fixed_timestep::FixedTimestep, game::Game, graphics};
 
-use super::graphics::{camera::Camera, texture_manager::TextureManager, util::master_graphics_list::MasterGraphicsList};
+use super::framework_builder::FrameworkBuilder;
+use super::graphics::{camera::Camera, debug_draw::DebugDraw, gl_check, overdraw, post_process::PostProcessor, shader_manager::ShaderManager, texture_manager::{TextureManager, TextureSettings}, util::master_graphics_list::MasterGraphicsList};
+
+/// How the scene adapts when the framebuffer size differs from the design resolution
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScalingPolicy {
+    /// Fill the window, distorting the image if the aspect ratio changes
+    Stretch,
+    /// Keep the design aspect ratio, with black bars filling the rest of the window
+    Letterbox,
+    /// Fill the window and show more or less of the world to match its aspect ratio
+    #[default]
+    Expand,
+}
+
+/// What draw_frame shows, for inspecting geometry and fill rate without touching the game's shaders
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderMode {
+    #[default]
+    Normal,
+    /// Triangle outlines only. Desktop GL, GLES has no glPolygonMode.
+    Wireframe,
+    /// Every object drawn additively in one flat color on black, so brighter areas were filled more often
+    Overdraw,
+}
 
 pub struct FrameworkController {
     master_graphics_list: Arc<RwLock<MasterGraphicsList>>,
     projection_matrix: Matrix4<f32>,
     texture_manager: Arc<RwLock<TextureManager>>,
+    shader_manager: Arc<RwLock<ShaderManager>>,
     camera: Arc<RwLock<Camera>>,
+    debug_draw: Arc<RwLock<DebugDraw>>, // Shapes queued for this frame, drawn on top of everything
     width: f32,
     height: f32,
+    design_width: f32, // The resolution the game was laid out for, used by the Stretch and Letterbox policies
+    design_height: f32,
+    scaling_policy: ScalingPolicy,
+    viewport: (i32, i32, i32, i32), // x, y, width, height within the framebuffer
+    fixed_timestep: FixedTimestep, // Drives Game::fixed_update in run()
+    headless: bool, // No GL context, so nothing is drawn
+    exit_requested: bool,
+    post_processor: PostProcessor, // Fullscreen effects applied to the finished frame
+    srgb: bool, // Gamma-correct rendering, see set_srgb
+    render_mode: RenderMode,
+    overdraw_program: Option<GLuint>, // Compiled the first time Overdraw is selected, owned by the shader manager
 }
 
 impl FrameworkController {
-    pub fn new() -> Self {
+    /// Sets up GL state for rendering. Needs a current GL context (e.g. a GLFW window made current) to load functions from.
+    pub fn new() -> EngineResult<Self> {
         // There is something holy about this resolution
         let width: f32 = 640.0; 
         let height: f32 = 480.0;
@@ -26,21 +65,162 @@ impl FrameworkController {
 
         // Load OpenGL functions
         graphics::glfw::load_gl_symbols();
+        if !gl::Enable::is_loaded() || !gl::Clear::is_loaded() {
+            return Err(EngineError::GlInit("OpenGL functions could not be loaded, is a GL context current?".to_string()));
+        }
+
+        gl_check::set_context_alive(true);
 
+        let gl_es = graphics::compile::detect_gl_es();
         unsafe {
+            // For the game's own draws. draw_all orders objects by layer and turns the depth test off for its pass.
             gl::Enable(gl::DEPTH_TEST);
             gl::DepthFunc(gl::LESS);
-            gl::ClearDepth(1.0);
+            // Straight alpha blending, so vertex color and texture alpha fade and tint (particles, trails, sprites)
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+            // glClearDepth is desktop only, GLES has the float variant
+            if gl_es && gl::ClearDepthf::is_loaded() {
+                gl::ClearDepthf(1.0);
+            } else {
+                gl::ClearDepth(1.0);
+            }
         }
 
-        Self {
+        Ok(Self {
             master_graphics_list: Arc::new(RwLock::new(MasterGraphicsList::new())),
             projection_matrix,
             texture_manager: Arc::new(RwLock::new(TextureManager::new())),
+            shader_manager: Arc::new(RwLock::new(ShaderManager::new())),
             camera: Arc::new(RwLock::new(Camera::new(0.1))),
+            debug_draw: Arc::new(RwLock::new(DebugDraw::new())),
             width,
             height,
+            design_width: width,
+            design_height: height,
+            scaling_policy: ScalingPolicy::default(),
+            viewport: (0, 0, width as i32, height as i32),
+            fixed_timestep: FixedTimestep::new(60.0, 5),
+            headless: false,
+            exit_requested: false,
+            post_processor: PostProcessor::new(),
+            srgb: false,
+            render_mode: RenderMode::default(),
+            overdraw_program: None,
+        })
+    }
+
+    /// Sets up a controller without a window or GL context, for game-logic tests and dedicated servers.
+    /// Queued commands, the camera, timers, fixed updates and object animation, lifetimes and despawning all run,
+    /// but nothing is drawn. Graphics objects can be created as usual, they just skip allocating GL buffers.
+    pub fn new_headless() -> Self {
+        let width: f32 = 640.0;
+        let height: f32 = 480.0;
+
+        Self {
+            master_graphics_list: Arc::new(RwLock::new(MasterGraphicsList::new())),
+            projection_matrix: Self::init_projection_matrix(width, height),
+            texture_manager: Arc::new(RwLock::new(TextureManager::new())),
+            shader_manager: Arc::new(RwLock::new(ShaderManager::new())),
+            camera: Arc::new(RwLock::new(Camera::new(0.1))),
+            debug_draw: Arc::new(RwLock::new(DebugDraw::new())),
+            width,
+            height,
+            design_width: width,
+            design_height: height,
+            scaling_policy: ScalingPolicy::default(),
+            viewport: (0, 0, width as i32, height as i32),
+            fixed_timestep: FixedTimestep::new(60.0, 5),
+            headless: true,
+            exit_requested: false,
+            post_processor: PostProcessor::new(),
+            srgb: false,
+            render_mode: RenderMode::default(),
+            overdraw_program: None,
+        }
+    }
+
+    /// Whether the context is OpenGL ES, in which case shaders are compiled as GLSL ES 3.00
+    pub fn is_gl_es(&self) -> bool {
+        !self.headless && graphics::compile::is_gl_es()
+    }
+
+    /// Tells the framework the GL context is gone, e.g. after destroying a window created without FrameworkBuilder.
+    /// From then on dropped GL resources are simply forgotten instead of deleted, which would crash. WindowContext does this itself.
+    pub fn notify_context_lost(&self) {
+        gl_check::set_context_alive(false);
+    }
+
+    pub fn is_headless(&self) -> bool {
+        self.headless
+    }
+
+    /// Asks run() or run_headless() to stop after the current frame
+    pub fn request_exit(&mut self) {
+        self.exit_requested = true;
+    }
+
+    /// Turns multisampled rasterization on or off at runtime. Only matters when the window was created with
+    /// FrameworkBuilder::samples, where it starts on. GLES always multisamples such framebuffers, so this does nothing there.
+    pub fn set_multisampling(&self, enabled: bool) {
+        if self.headless || self.is_gl_es() {
+            return;
+        }
+        unsafe {
+            if enabled {
+                gl::Enable(gl::MULTISAMPLE);
+            } else {
+                gl::Disable(gl::MULTISAMPLE);
+            }
+        }
+    }
+
+    /// Renders gamma-correctly: shaders blend and tint in linear space and GL encodes the result to sRGB on write,
+    /// and textures loaded from now on are stored as SRGB8_ALPHA8 so they are decoded to linear when sampled.
+    /// The window needs an sRGB-capable framebuffer, see FrameworkBuilder::srgb. On GLES only the texture format changes.
+    pub fn set_srgb(&mut self, enabled: bool) {
+        self.srgb = enabled;
+        let texture_manager = self.texture_manager.read().unwrap();
+        texture_manager.set_default_settings(TextureSettings { srgb: enabled, ..texture_manager.get_default_settings() });
+        self.post_processor.set_srgb(enabled);
+
+        if self.headless || self.is_gl_es() {
+            return;
+        }
+        unsafe {
+            if enabled {
+                gl::Enable(gl::FRAMEBUFFER_SRGB);
+            } else {
+                gl::Disable(gl::FRAMEBUFFER_SRGB);
+            }
+        }
+    }
+
+    pub fn is_srgb(&self) -> bool {
+        self.srgb
+    }
+
+    /// Switches how the scene is drawn. Fails when headless, for Wireframe on GLES, or if the overdraw shader doesn't compile.
+    pub fn set_render_mode(&mut self, render_mode: RenderMode) -> EngineResult<()> {
+        if self.headless {
+            return Err(EngineError::GlInit("Render modes need a GL context, the controller is headless".to_string()));
         }
+        match render_mode {
+            RenderMode::Wireframe if self.is_gl_es() => {
+                return Err(EngineError::GlInit("Wireframe needs glPolygonMode, which OpenGL ES doesn't have".to_string()));
+            }
+            RenderMode::Overdraw if self.overdraw_program.is_none() => {
+                let program = self.shader_manager.read().unwrap().get_or_compile(overdraw::OVERDRAW_VERTEX_SHADER, overdraw::OVERDRAW_FRAGMENT_SHADER)?;
+                self.overdraw_program = Some(program);
+            }
+            _ => {}
+        }
+        self.render_mode = render_mode;
+        Ok(())
+    }
+
+    pub fn get_render_mode(&self) -> RenderMode {
+        self.render_mode
     }
 
     fn calculate_projection_matrix(width: f32, height: f32, camera_position: &Vector3<f32>) -> Matrix4<f32> {
@@ -64,34 +244,310 @@ impl FrameworkController {
     }
     
 
-    /// Sets the resolution of the openGL viewport and updates the projection matrix
+    /// Sets the framebuffer resolution, then updates the openGL viewport and projection matrix according to the scaling policy
     pub fn set_resolution(&mut self, width: f32, height: f32) {
+        if width <= 0.0 || height <= 0.0 {
+            return; // Minimized windows report a zero sized framebuffer
+        }
         self.width = width;
         self.height = height;
-        self.projection_matrix = Self::calculate_projection_matrix(width, height, &self.camera.read().unwrap().get_position());
+        self.viewport = self.calculate_viewport();
+        let (projection_width, projection_height) = self.get_projection_size();
+        self.projection_matrix = Self::calculate_projection_matrix(projection_width, projection_height, &self.camera.read().unwrap().get_position());
+        if self.headless {
+            return;
+        }
         unsafe {
-            gl::Viewport(0, 0, width as i32, height as i32);  // Update the OpenGL viewport
+            gl::Viewport(self.viewport.0, self.viewport.1, self.viewport.2, self.viewport.3);  // Update the OpenGL viewport
         }
     }
 
-    /// Returns true if the window should close
+    /// Sets the resolution the game was laid out for, which Stretch and Letterbox keep the aspect ratio of
+    pub fn set_design_resolution(&mut self, width: f32, height: f32) {
+        self.design_width = width;
+        self.design_height = height;
+        self.set_resolution(self.width, self.height);
+    }
+
+    pub fn get_design_resolution(&self) -> (f32, f32) {
+        (self.design_width, self.design_height)
+    }
+
+    pub fn set_scaling_policy(&mut self, scaling_policy: ScalingPolicy) {
+        self.scaling_policy = scaling_policy;
+        self.set_resolution(self.width, self.height);
+    }
+
+    pub fn get_scaling_policy(&self) -> ScalingPolicy {
+        self.scaling_policy
+    }
+
+    /// The framebuffer resolution
+    pub fn get_resolution(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    /// The area of the framebuffer the scene is drawn into, as (x, y, width, height)
+    pub fn get_viewport(&self) -> (i32, i32, i32, i32) {
+        self.viewport
+    }
+
+    /// Keeps the viewport and projection in sync with the window. run() calls this for every event;
+    /// applications driving their own loop should pass their events through it too.
+    pub fn handle_window_event(&mut self, event: &glfw::WindowEvent) {
+        if let glfw::WindowEvent::FramebufferSize(width, height) = event {
+            self.set_resolution(*width as f32, *height as f32);
+        }
+    }
+
+    fn calculate_viewport(&self) -> (i32, i32, i32, i32) {
+        match self.scaling_policy {
+            ScalingPolicy::Stretch | ScalingPolicy::Expand => (0, 0, self.width as i32, self.height as i32),
+            ScalingPolicy::Letterbox => {
+                let scale = (self.width / self.design_width).min(self.height / self.design_height);
+                let viewport_width = self.design_width * scale;
+                let viewport_height = self.design_height * scale;
+                (
+                    ((self.width - viewport_width) / 2.0) as i32,
+                    ((self.height - viewport_height) / 2.0) as i32,
+                    viewport_width as i32,
+                    viewport_height as i32,
+                )
+            }
+        }
+    }
+
+    /// The size whose aspect ratio the projection is built from
+    fn get_projection_size(&self) -> (f32, f32) {
+        match self.scaling_policy {
+            ScalingPolicy::Stretch | ScalingPolicy::Letterbox => (self.design_width, self.design_height),
+            ScalingPolicy::Expand => (self.width, self.height),
+        }
+    }
+
+    /// Starts configuring a window and controller, see FrameworkBuilder
+    pub fn builder() -> FrameworkBuilder {
+        FrameworkBuilder::new()
+    }
+
+    pub fn set_window_title(&self, window: &mut glfw::PWindow, title: &str) {
+        window.set_title(title);
+    }
+
+    /// Sets the window and taskbar icon from an image file. Some platforms (e.g. macOS) ignore window icons.
+    pub fn set_window_icon(&self, window: &mut glfw::PWindow, path: &str) -> EngineResult<()> {
+        let img = image::open(path).map_err(|e| EngineError::Image { path: path.to_owned(), source: e })?.to_rgba8();
+        let (width, height) = img.dimensions();
+        let pixels = img.pixels().map(|pixel| u32::from_le_bytes(pixel.0)).collect();
+        window.set_icon_from_pixels(vec![glfw::PixelImage { width, height, pixels }]);
+        Ok(())
+    }
+
+    /// Limits how small and large the user can resize the window. None leaves that bound unconstrained.
+    pub fn set_window_size_limits(&self, window: &mut glfw::PWindow, min_size: Option<(u32, u32)>, max_size: Option<(u32, u32)>) {
+        window.set_size_limits(
+            min_size.map(|(width, _)| width),
+            min_size.map(|(_, height)| height),
+            max_size.map(|(width, _)| width),
+            max_size.map(|(_, height)| height),
+        );
+    }
+
+    /// Normal shows the cursor, Hidden hides it over the window, Disabled hides and locks it for mouse-look style input
+    pub fn set_cursor_mode(&self, window: &mut glfw::PWindow, cursor_mode: glfw::CursorMode) {
+        window.set_cursor_mode(cursor_mode);
+    }
+
+    /// Switches to one of the system cursor shapes, or back to the default arrow with None
+    pub fn set_standard_cursor(&self, window: &mut glfw::PWindow, cursor: Option<glfw::StandardCursor>) {
+        window.set_cursor(cursor.map(glfw::Cursor::standard));
+    }
+
+    /// Runs the main loop until the window is asked to close: polls events, runs the game's fixed updates and update,
+    /// draws the scene and the game's UI, then swaps buffers.
+    pub fn run<G: Game>(&mut self, glfw: &mut glfw::Glfw, window: &mut glfw::PWindow, events: &glfw::GlfwReceiver<(f64, glfw::WindowEvent)>, game: &mut G) -> EngineResult<()> {
+        game.init(self)?;
+
+        self.exit_requested = false;
+        let mut last_frame = glfw.get_time();
+        while !window.should_close() && !self.exit_requested {
+            glfw.poll_events();
+            for (_, event) in glfw::flush_messages(events) {
+                self.handle_window_event(&event);
+                game.handle_event(self, window, &event);
+            }
+
+            let now = glfw.get_time();
+            let delta_time = (now - last_frame) as f32;
+            last_frame = now;
+
+            let fixed_steps = self.fixed_timestep.accumulate(delta_time);
+            let fixed_delta_time = self.fixed_timestep.get_step_duration();
+            for _ in 0..fixed_steps {
+                game.fixed_update(self, fixed_delta_time);
+            }
+
+            game.update(self, delta_time);
+
+            self.draw_frame(delta_time);
+            game.render_ui(self);
+            window.swap_buffers();
+        }
+
+        game.shutdown(self);
+        self.shutdown();
+        Ok(())
+    }
+
+    /// Runs the main loop without a window until request_exit() is called, or until max_frames frames have run.
+    /// With a fixed_delta_time every frame advances by exactly that much and the loop runs as fast as it can,
+    /// which keeps tests deterministic; without one, frames are timed by the real clock.
+    pub fn run_headless<G: Game>(&mut self, game: &mut G, fixed_delta_time: Option<f32>, max_frames: Option<u64>) -> EngineResult<()> {
+        game.init(self)?;
+
+        self.exit_requested = false;
+        let mut last_frame = Instant::now();
+        let mut frames: u64 = 0;
+        while !self.exit_requested && max_frames.is_none_or(|max_frames| frames < max_frames) {
+            let now = Instant::now();
+            let delta_time = fixed_delta_time.unwrap_or_else(|| now.duration_since(last_frame).as_secs_f32());
+            last_frame = now;
+
+            let fixed_steps = self.fixed_timestep.accumulate(delta_time);
+            let fixed_delta_time = self.fixed_timestep.get_step_duration();
+            for _ in 0..fixed_steps {
+                game.fixed_update(self, fixed_delta_time);
+            }
+
+            game.update(self, delta_time);
+            self.draw_frame(delta_time);
+            frames += 1;
+        }
+
+        game.shutdown(self);
+        self.shutdown();
+        Ok(())
+    }
+
+    /// Appends a fullscreen effect (bloom, grayscale, CRT...) to the end of the post-processing chain.
+    /// The fragment shader gets `in vec2 TexCoord`, the previous pass as `uniform sampler2D screenTexture`,
+    /// and optionally `uniform vec2 resolution` and `uniform float time`. Adding an existing name replaces that effect in place.
+    pub fn add_post_effect(&mut self, name: &str, fragment_shader_src: &str) -> EngineResult<()> {
+        if self.headless {
+            return Err(EngineError::GlInit("Post effects need a GL context, the controller is headless".to_string()));
+        }
+        self.post_processor.add_effect(name, fragment_shader_src)
+    }
+
+    /// Removes an effect from the chain, returning whether it existed
+    pub fn remove_post_effect(&mut self, name: &str) -> bool {
+        self.post_processor.remove_effect(name)
+    }
+
+    /// Effect names in the order they are applied
+    pub fn get_post_effects(&self) -> Vec<String> {
+        self.post_processor.get_effect_names()
+    }
+
+    /// Sets how often Game::fixed_update runs, and how many catch-up steps a slow frame may run
+    pub fn set_fixed_update_rate(&mut self, hz: f32, max_steps: usize) {
+        self.fixed_timestep.set_hz(hz);
+        self.fixed_timestep.set_max_steps(max_steps);
+    }
+
+    /// Draws a frame and swaps buffers, for applications that drive their own loop instead of using run()
     pub fn render(&mut self, window: &mut glfw::PWindow, delta_time: f32) {
+        self.draw_frame(delta_time);
+
+        // Swap buffers
+        window.swap_buffers();
+    }
+
+    /// Clears the screen and draws every object, without swapping buffers
+    pub fn draw_frame(&mut self, delta_time: f32) {
+        // Apply any adds/removes/modifications queued since the last frame
+        self.master_graphics_list.read().unwrap().flush_commands();
+
         // Update the camera and projection
         let mut camera_write = self.camera.write().unwrap();
         camera_write.update_position(&self.master_graphics_list.read().unwrap());
-        self.projection_matrix = Self::calculate_projection_matrix(self.width, self.height, &camera_write.get_position());
+        let (projection_width, projection_height) = self.get_projection_size();
+        self.projection_matrix = Self::calculate_projection_matrix(projection_width, projection_height, &camera_write.get_position());
+
+        // Animation, lifetimes and despawning, which game logic depends on even when nothing is drawn
+        let overlay_projection = Self::init_projection_matrix(projection_width, projection_height); // No camera
+        let mut master_graphics_list = self.master_graphics_list.write().unwrap();
+        master_graphics_list.set_overlay_projection(overlay_projection);
+        master_graphics_list.update_all(&self.projection_matrix, delta_time);
+        drop(master_graphics_list);
+
+        if self.headless {
+            self.debug_draw.write().unwrap().clear(); // Nothing to draw it on, but don't let it pile up
+            return;
+        }
+
+        // With post effects the scene goes to an offscreen target first, and the effects write the viewport at the end
+        let post_processing = self.post_processor.is_active() && match self.post_processor.begin(self.viewport.2, self.viewport.3) {
+            Ok(()) => true,
+            Err(e) => {
+                log::error!("Skipping post effects this frame: {}", e);
+                unsafe { gl::BindFramebuffer(gl::FRAMEBUFFER, 0); }
+                false
+            }
+        };
 
         // Render here
         unsafe {
-            gl::ClearColor(0.2, 0.3, 0.3, 1.0); // Set background color
+            if self.scaling_policy == ScalingPolicy::Letterbox && !post_processing {
+                // Black bars outside the viewport, then limit the background clear to the viewport
+                gl::ClearColor(0.0, 0.0, 0.0, 1.0);
+                gl::Clear(gl::COLOR_BUFFER_BIT);
+                gl::Enable(gl::SCISSOR_TEST);
+                gl::Scissor(self.viewport.0, self.viewport.1, self.viewport.2, self.viewport.3);
+            }
+            if self.render_mode == RenderMode::Overdraw {
+                gl::ClearColor(0.0, 0.0, 0.0, 1.0); // Black, so brightness is only the accumulated fills
+            } else if self.srgb {
+                gl::ClearColor(0.033, 0.073, 0.073, 1.0); // The same background, linearized since GL encodes it on write
+            } else {
+                gl::ClearColor(0.2, 0.3, 0.3, 1.0); // Set background color
+            }
             gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);    // Clear the screen + depth buffer
+            if self.scaling_policy == ScalingPolicy::Letterbox && !post_processing {
+                gl::Disable(gl::SCISSOR_TEST);
+            }
         }
+        gl_check::check_gl_errors("frame clear");
 
         // Draw
-        self.master_graphics_list.write().unwrap().draw_all(&self.projection_matrix, delta_time);
+        match self.render_mode {
+            RenderMode::Normal => self.master_graphics_list.write().unwrap().draw_all(&self.projection_matrix),
+            RenderMode::Wireframe => unsafe {
+                gl::PolygonMode(gl::FRONT_AND_BACK, gl::LINE);
+                self.master_graphics_list.write().unwrap().draw_all(&self.projection_matrix);
+                gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL);
+            },
+            RenderMode::Overdraw => unsafe {
+                gl::BlendFunc(gl::ONE, gl::ONE); // Additive, so every fill adds up
+                self.master_graphics_list.write().unwrap().draw_all_with_program(&self.projection_matrix, self.overdraw_program);
+                gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+            },
+        }
 
-        // Swap buffers
-        window.swap_buffers();
+        if post_processing {
+            if self.scaling_policy == ScalingPolicy::Letterbox {
+                // The final pass only covers the viewport, so the bars still need clearing
+                unsafe {
+                    gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+                    gl::ClearColor(0.0, 0.0, 0.0, 1.0);
+                    gl::Clear(gl::COLOR_BUFFER_BIT);
+                }
+            }
+            self.post_processor.finish(self.viewport, delta_time);
+        }
+
+        // After post-processing, so debug shapes stay crisp
+        self.debug_draw.write().unwrap().render(&self.projection_matrix);
     }
 
     pub fn shutdown(&self) {
@@ -102,6 +558,15 @@ impl FrameworkController {
         return self.texture_manager.clone();
     }
 
+    pub fn get_shader_manager(&self) -> Arc<RwLock<ShaderManager>> {
+        self.shader_manager.clone()
+    }
+
+    /// Queue lines, rects and circles here to see them for one frame
+    pub fn get_debug_draw(&self) -> Arc<RwLock<DebugDraw>> {
+        self.debug_draw.clone()
+    }
+
     pub fn get_master_graphics_list(&self) -> Arc<RwLock<MasterGraphicsList>> {
         return self.master_graphics_list.clone();
     }
@@ -0,0 +1,25 @@
+use super::{error::EngineResult, framework_controller::FrameworkController};
+
+/// Implemented by applications and handed to FrameworkController::run, which owns the main loop.
+/// Only update is required, everything else defaults to doing nothing.
+pub trait Game {
+    /// Called once before the first frame, e.g. to load textures and create objects
+    fn init(&mut self, _controller: &mut FrameworkController) -> EngineResult<()> {
+        Ok(())
+    }
+
+    /// Called for every window event (keys, mouse, resize) before the frame is updated
+    fn handle_event(&mut self, _controller: &mut FrameworkController, _window: &mut glfw::PWindow, _event: &glfw::WindowEvent) {}
+
+    /// Called zero or more times per frame at the controller's fixed update rate, for physics and other deterministic logic
+    fn fixed_update(&mut self, _controller: &mut FrameworkController, _fixed_delta_time: f32) {}
+
+    /// Called once per frame with the time since the last frame
+    fn update(&mut self, controller: &mut FrameworkController, delta_time: f32);
+
+    /// Called after the scene is drawn and before the buffers are swapped, for UI drawn on top
+    fn render_ui(&mut self, _controller: &mut FrameworkController) {}
+
+    /// Called once after the loop exits, before the graphics list is cleared
+    fn shutdown(&mut self, _controller: &mut FrameworkController) {}
+}
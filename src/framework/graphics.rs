@@ -1,6 +1,19 @@
-pub mod glfw;
+pub(crate) mod glfw;
 pub mod internal_object;
 pub mod util;
 pub mod texture_manager;
-mod compile;
-pub mod camera;
\ No newline at end of file
+pub mod background_loader;
+pub(crate) mod compile;
+pub mod gl_check;
+pub mod camera;
+pub mod text;
+pub mod particles;
+pub mod trail;
+pub mod tilemap;
+pub mod instanced;
+pub mod debug_draw;
+pub mod nine_slice;
+pub(crate) mod post_process;
+pub(crate) mod overdraw;
+pub mod shader_manager;
+pub mod shapes;
\ No newline at end of file
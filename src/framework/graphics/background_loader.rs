@@ -0,0 +1,206 @@
+use std::{sync::{mpsc::{channel, Receiver, Sender}, Arc, RwLock}, thread::{self, JoinHandle}};
+
+use gl::types::{GLsync, GLuint};
+use glfw::Context;
+
+use crate::framework::{asset_manager::AssetManager, error::{EngineError, EngineResult}};
+
+use super::{gl_check, internal_object::vbo::VBO, texture_manager::{TextureManager, TextureSettings}};
+
+enum LoadRequest {
+    Texture {
+        name: String,
+        asset_path: String,
+        settings: TextureSettings, // The manager's defaults when the load was queued
+    },
+    Mesh {
+        name: String,
+        vertex_data: Vec<f32>,
+        texture_coords: Vec<f32>,
+    },
+}
+
+/// A finished load, as handed out by BackgroundLoader::poll
+pub enum LoadedAsset {
+    /// Already stored in the TextureManager under the load's name
+    Texture(GLuint),
+    Mesh(UploadedMesh),
+}
+
+/// Vertex buffers uploaded on the loader thread, turned into a graphics object with Generic2DGraphicsObject::from_uploaded_mesh
+pub struct UploadedMesh {
+    pub(crate) vertex_data: Vec<f32>,
+    pub(crate) texture_coords: Vec<f32>,
+    pub(crate) position_vbo: VBO,
+    pub(crate) tex_vbo: VBO,
+}
+
+enum Uploaded {
+    Texture(GLuint, TextureSettings),
+    Mesh(UploadedMesh),
+}
+
+struct LoadResult {
+    name: String,
+    result: EngineResult<(Uploaded, usize)>, // The upload and its fence, as an address so it can cross threads
+}
+
+/// Decodes and uploads textures, and uploads vertex buffers, on a loader thread with its own GL context shared with
+/// the main window, so streaming assets in doesn't stall rendering. Each upload is fenced, and only handed out
+/// by poll() once the GPU has finished it, so nothing is drawn half uploaded.
+///
+/// VBOs are shared between the contexts but VAOs aren't, so a streamed mesh still gets its VAO on the render thread
+/// when it is passed to Generic2DGraphicsObject::from_uploaded_mesh.
+pub struct BackgroundLoader {
+    request_sender: Option<Sender<LoadRequest>>,
+    result_receiver: Receiver<LoadResult>,
+    pending_fences: Vec<(String, Uploaded, GLsync)>,
+    in_flight: usize, // Requests sent to the thread that haven't come back yet
+    texture_manager: Arc<RwLock<TextureManager>>,
+    thread: Option<JoinHandle<()>>,
+    _shared_window: glfw::PWindow, // Owns the loader's context, must outlive the thread
+}
+
+impl BackgroundLoader {
+    /// Creates a hidden window sharing the main window's context and starts the loader thread on it.
+    /// Must be called on the main thread, after GL has been loaded. Textures are read through asset_manager, so paks work.
+    pub fn new(glfw: &mut glfw::Glfw, window: &glfw::PWindow, texture_manager: Arc<RwLock<TextureManager>>, asset_manager: Arc<RwLock<AssetManager>>) -> EngineResult<Self> {
+        glfw.window_hint(glfw::WindowHint::Visible(false));
+        let shared = window.create_shared(1, 1, "rusted_open loader", glfw::WindowMode::Windowed);
+        glfw.window_hint(glfw::WindowHint::Visible(true));
+        let (mut shared_window, _) = shared.ok_or_else(|| EngineError::WindowCreation("Failed to create a shared context for the background loader".to_string()))?;
+
+        let (request_sender, request_receiver) = channel::<LoadRequest>();
+        let (result_sender, result_receiver) = channel();
+        let mut render_context = shared_window.render_context();
+
+        let thread = thread::spawn(move || {
+            render_context.make_current();
+
+            for request in request_receiver {
+                let (name, result) = match request {
+                    LoadRequest::Texture { name, asset_path, settings } => {
+                        let result = asset_manager.read().unwrap().read_bytes(&asset_path)
+                            .and_then(|bytes| image::load_from_memory(&bytes).map_err(|source| EngineError::Image { path: asset_path.clone(), source }))
+                            .map(|img| Uploaded::Texture(TextureManager::upload_texture(&img, settings), settings));
+                        (name, result)
+                    }
+                    LoadRequest::Mesh { name, vertex_data, texture_coords } => {
+                        let position_vbo = VBO::new(&vertex_data);
+                        let tex_vbo = VBO::new(&texture_coords);
+                        (name, Ok(Uploaded::Mesh(UploadedMesh { vertex_data, texture_coords, position_vbo, tex_vbo })))
+                    }
+                };
+
+                let result = result.map(|uploaded| unsafe {
+                    let fence = gl::FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0);
+                    gl::Flush(); // Make sure the fence reaches the GPU so the main context can see it signal
+                    (uploaded, fence as usize)
+                });
+
+                if result_sender.send(LoadResult { name, result }).is_err() {
+                    break; // Loader was dropped
+                }
+            }
+
+            glfw::make_context_current(None);
+        });
+
+        Ok(BackgroundLoader {
+            request_sender: Some(request_sender),
+            result_receiver,
+            pending_fences: Vec::new(),
+            in_flight: 0,
+            texture_manager,
+            thread: Some(thread),
+            _shared_window: shared_window,
+        })
+    }
+
+    /// Queues a texture to be loaded under name, from an asset path resolved by the AssetManager.
+    /// It becomes available from the TextureManager once poll() reports it.
+    pub fn load_texture(&mut self, name: &str, asset_path: &str) {
+        let settings = self.texture_manager.read().unwrap().get_default_settings();
+        self.send(LoadRequest::Texture { name: name.to_owned(), asset_path: asset_path.to_owned(), settings });
+    }
+
+    /// Queues a mesh's position and texture coordinate buffers to be uploaded. poll() hands them out as LoadedAsset::Mesh.
+    pub fn load_mesh(&mut self, name: &str, vertex_data: Vec<f32>, texture_coords: Vec<f32>) {
+        self.send(LoadRequest::Mesh { name: name.to_owned(), vertex_data, texture_coords });
+    }
+
+    fn send(&mut self, request: LoadRequest) {
+        if let Some(sender) = &self.request_sender {
+            if sender.send(request).is_ok() {
+                self.in_flight += 1;
+            }
+        }
+    }
+
+    /// Call once per frame on the render thread. Hands finished textures to the TextureManager and returns
+    /// the name and result of every load that completed since the last poll.
+    pub fn poll(&mut self) -> Vec<(String, EngineResult<LoadedAsset>)> {
+        let mut completed = Vec::new();
+
+        for load in self.result_receiver.try_iter() {
+            self.in_flight -= 1;
+            match load.result {
+                Ok((uploaded, fence)) => self.pending_fences.push((load.name, uploaded, fence as GLsync)),
+                Err(e) => completed.push((load.name, Err(e))),
+            }
+        }
+
+        let texture_manager = self.texture_manager.read().unwrap();
+        let mut still_pending = Vec::new();
+        for (name, uploaded, fence) in self.pending_fences.drain(..) {
+            let status = unsafe { gl::ClientWaitSync(fence, 0, 0) };
+            if status != gl::ALREADY_SIGNALED && status != gl::CONDITION_SATISFIED {
+                still_pending.push((name, uploaded, fence));
+                continue;
+            }
+
+            unsafe { gl::DeleteSync(fence); }
+            let asset = match uploaded {
+                Uploaded::Texture(texture_id, settings) => {
+                    texture_manager.insert_uploaded_texture(&name, texture_id, settings);
+                    LoadedAsset::Texture(texture_id)
+                }
+                Uploaded::Mesh(mesh) => LoadedAsset::Mesh(mesh),
+            };
+            completed.push((name, Ok(asset)));
+        }
+        self.pending_fences = still_pending;
+
+        completed
+    }
+
+    /// Whether any queued or fenced loads are still outstanding
+    pub fn has_pending(&self) -> bool {
+        self.in_flight > 0 || !self.pending_fences.is_empty()
+    }
+}
+
+impl Drop for BackgroundLoader {
+    fn drop(&mut self) {
+        // Closing the channel ends the thread's loop, then it releases its context before the window is dropped
+        self.request_sender.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+
+        // Uploads nobody polled for yet, including ones the thread finished while it was being joined
+        let unpolled = self.result_receiver.try_iter().filter_map(|load| load.result.ok()).map(|(uploaded, fence)| (uploaded, fence as GLsync));
+        let pending: Vec<(Uploaded, GLsync)> = self.pending_fences.drain(..).map(|(_, uploaded, fence)| (uploaded, fence)).chain(unpolled).collect();
+        if !gl_check::is_context_alive() {
+            return; // The context and everything in it is already gone
+        }
+        for (uploaded, fence) in pending {
+            unsafe {
+                gl::DeleteSync(fence);
+                if let Uploaded::Texture(texture_id, _) = uploaded {
+                    gl::DeleteTextures(1, &texture_id); // Never handed to the TextureManager, so nothing else owns it
+                }
+            }
+        }
+    }
+}
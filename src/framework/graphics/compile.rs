@@ -2,12 +2,63 @@ use gl::types::GLenum;
 use gl::types::GLuint;
 use gl::types::GLint;
 use gl::types::GLchar;
+use gl::types::GLsizei;
 use std::ffi::CString;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::framework::error::{EngineError, EngineResult};
+
+use super::gl_check;
+
+// Set once the context is known to be OpenGL ES, so desktop GLSL gets rewritten before compiling
+static GL_ES: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn set_gl_es(gl_es: bool) {
+    GL_ES.store(gl_es, Ordering::Relaxed);
+}
+
+pub(crate) fn is_gl_es() -> bool {
+    GL_ES.load(Ordering::Relaxed)
+}
+
+/// On GLES, swaps the desktop #version line for "#version 300 es" plus default precisions,
+/// so the same shader sources work on both. Sources already written for ES are left alone.
+fn prepare_source(source: &str) -> String {
+    let first_line = source.trim_start().lines().next().unwrap_or("");
+    if !is_gl_es() || (first_line.starts_with("#version") && first_line.trim_end().ends_with(" es")) {
+        return source.to_owned();
+    }
+
+    let es_header = "#version 300 es\nprecision highp float;\nprecision highp int;\n";
+    let body = match source.trim_start().strip_prefix("#version") {
+        Some(rest) => rest.split_once('\n').map(|(_, body)| body).unwrap_or(""),
+        None => source,
+    };
+    format!("{}{}", es_header, body)
+}
+
+/// Reads GL_VERSION from the current context to tell desktop GL from GLES, and remembers the answer for shader compilation
+pub(crate) fn detect_gl_es() -> bool {
+    let version = unsafe {
+        let version_ptr = gl::GetString(gl::VERSION);
+        if version_ptr.is_null() {
+            String::new()
+        } else {
+            std::ffi::CStr::from_ptr(version_ptr as *const std::ffi::c_char).to_string_lossy().into_owned()
+        }
+    };
+    log::info!("OpenGL version: {}", version);
+
+    let gl_es = version.starts_with("OpenGL ES");
+    set_gl_es(gl_es);
+    gl_es
+}
+
+fn compile_shader(source: &str, shader_type: GLenum) -> EngineResult<GLuint> {
+    let c_str = CString::new(prepare_source(source)).map_err(|_| EngineError::ShaderCompile("Shader source contains a null byte".to_string()))?;
 
-fn compile_shader(source: &str, shader_type: GLenum) -> GLuint {
     unsafe {
         let shader = gl::CreateShader(shader_type);
-        let c_str = CString::new(source).unwrap();
         gl::ShaderSource(shader, 1, &c_str.as_ptr(), std::ptr::null());
         gl::CompileShader(shader);
 
@@ -15,65 +66,64 @@ fn compile_shader(source: &str, shader_type: GLenum) -> GLuint {
         let mut success = GLint::default();
         gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut success);
         if success == 0 {
-            let mut info_log = Vec::with_capacity(512);
-            info_log.set_len(511); // Reserve space for null terminator
+            let mut info_log = vec![0u8; 512];
+            let mut log_length: GLsizei = 0;
             gl::GetShaderInfoLog(
                 shader,
-                512,
-                std::ptr::null_mut(),
+                info_log.len() as GLsizei,
+                &mut log_length,
                 info_log.as_mut_ptr() as *mut GLchar,
             );
+            info_log.truncate(log_length.max(0) as usize);
+            gl::DeleteShader(shader);
 
-            // Print raw bytes if UTF-8 decoding fails
-            match std::str::from_utf8(&info_log) {
-                Ok(err_msg) => panic!("Shader compilation failed: {}", err_msg),
-                Err(_) => {
-                    // Printing the raw byte values to diagnose the issue
-                    panic!(
-                        "Shader compilation failed. Error log contains non-UTF-8 characters: {:?}",
-                        info_log
-                    );
-                }
-            }
+            // Lossy so a log with non-UTF-8 bytes still tells us something
+            return Err(EngineError::ShaderCompile(String::from_utf8_lossy(&info_log).into_owned()));
         }
 
-        shader
+        Ok(shader)
     }
 }
 
 
-pub fn create_shader_program(vertex_src: &str, fragment_src: &str) -> GLuint {
-    unsafe {
-        let vertex_shader = compile_shader(vertex_src, gl::VERTEX_SHADER);
-        let fragment_shader = compile_shader(fragment_src, gl::FRAGMENT_SHADER);
+pub fn create_shader_program(vertex_src: &str, fragment_src: &str) -> EngineResult<GLuint> {
+    let vertex_shader = compile_shader(vertex_src, gl::VERTEX_SHADER)?;
+    let fragment_shader = match compile_shader(fragment_src, gl::FRAGMENT_SHADER) {
+        Ok(fragment_shader) => fragment_shader,
+        Err(e) => {
+            unsafe { gl::DeleteShader(vertex_shader); }
+            return Err(e);
+        }
+    };
 
+    unsafe {
         let shader_program = gl::CreateProgram();
         gl::AttachShader(shader_program, vertex_shader);
         gl::AttachShader(shader_program, fragment_shader);
         gl::LinkProgram(shader_program);
 
+        // Clean up shaders, the program keeps what it needs
+        gl::DeleteShader(vertex_shader);
+        gl::DeleteShader(fragment_shader);
+
         // Check for linking errors
         let mut success = GLint::default();
         gl::GetProgramiv(shader_program, gl::LINK_STATUS, &mut success);
         if success == 0 {
-            let mut info_log = Vec::with_capacity(512);
-            info_log.set_len(511); // Reserve space for null terminator
+            let mut info_log = vec![0u8; 512];
+            let mut log_length: GLsizei = 0;
             gl::GetProgramInfoLog(
                 shader_program,
-                512,
-                std::ptr::null_mut(),
+                info_log.len() as GLsizei,
+                &mut log_length,
                 info_log.as_mut_ptr() as *mut GLchar,
             );
-            panic!(
-                "Shader program linking failed: {}",
-                std::str::from_utf8(&info_log).unwrap()
-            );
+            info_log.truncate(log_length.max(0) as usize);
+            gl::DeleteProgram(shader_program);
+            return Err(EngineError::ShaderLink(String::from_utf8_lossy(&info_log).into_owned()));
         }
+        gl_check::check_gl_errors("shader program creation");
 
-        // Clean up shaders
-        gl::DeleteShader(vertex_shader);
-        gl::DeleteShader(fragment_shader);
-
-        shader_program
+        Ok(shader_program)
     }
-}
+}
\ No newline at end of file
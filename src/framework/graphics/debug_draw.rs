@@ -0,0 +1,198 @@
+use std::{f32::consts::PI, ffi::CString};
+
+use gl::types::GLuint;
+use nalgebra::{Matrix4, Vector2};
+
+use super::{compile::create_shader_program, gl_check, internal_object::{vao::VAO, vbo::VBO}};
+
+const DEBUG_VERTEX_SHADER: &str = r#"
+#version 330 core
+layout(location = 0) in vec2 position;
+layout(location = 1) in vec4 color;
+
+uniform mat4 projection;
+
+out vec4 LineColor;
+
+void main() {
+    gl_Position = projection * vec4(position, 0.0, 1.0);
+    LineColor = color;
+}
+"#;
+
+const DEBUG_FRAGMENT_SHADER: &str = r#"
+#version 330 core
+in vec4 LineColor;
+out vec4 FragColor;
+
+void main() {
+    FragColor = LineColor;
+}
+"#;
+
+// Segments used for circles when the caller doesn't care
+const DEFAULT_CIRCLE_SEGMENTS: usize = 24;
+
+struct DebugDrawResources {
+    shader_program: GLuint,
+    vao: VAO,
+    position_vbo: VBO,
+    color_vbo: VBO,
+}
+
+/// Queues world-space lines, rectangle outlines and circles for a single frame, e.g. to see collision shapes,
+/// velocities and paths. The FrameworkController draws the queue on top of everything after the main pass and then empties it,
+/// so shapes have to be queued again every frame they should stay visible.
+pub struct DebugDraw {
+    positions: Vec<f32>, // x, y per vertex, two vertices per line
+    colors: Vec<f32>, // r, g, b, a per vertex
+    enabled: bool,
+    resources: Option<DebugDrawResources>, // Created on the first frame with something to draw
+}
+
+impl DebugDraw {
+    pub fn new() -> Self {
+        DebugDraw {
+            positions: Vec::new(),
+            colors: Vec::new(),
+            enabled: true,
+            resources: None,
+        }
+    }
+
+    /// While disabled, queued shapes are dropped instead of drawn, so debug calls can stay in release builds
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.clear();
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn line(&mut self, start: Vector2<f32>, end: Vector2<f32>, color: [f32; 4]) {
+        if !self.enabled {
+            return;
+        }
+        self.positions.extend_from_slice(&[start.x, start.y, end.x, end.y]);
+        self.colors.extend_from_slice(&color);
+        self.colors.extend_from_slice(&color);
+    }
+
+    /// An arrow from origin along vector, e.g. a velocity
+    pub fn arrow(&mut self, origin: Vector2<f32>, vector: Vector2<f32>, color: [f32; 4]) {
+        let tip = origin + vector;
+        self.line(origin, tip, color);
+
+        let length = vector.norm();
+        if length <= f32::EPSILON {
+            return;
+        }
+        let back = -vector / length * (length * 0.2);
+        let side = Vector2::new(-back.y, back.x) * 0.5;
+        self.line(tip, tip + back + side, color);
+        self.line(tip, tip + back - side, color);
+    }
+
+    /// Outline of an axis-aligned rectangle
+    pub fn rect(&mut self, min: Vector2<f32>, max: Vector2<f32>, color: [f32; 4]) {
+        let top_left = Vector2::new(min.x, max.y);
+        let bottom_right = Vector2::new(max.x, min.y);
+        self.line(min, bottom_right, color);
+        self.line(bottom_right, max, color);
+        self.line(max, top_left, color);
+        self.line(top_left, min, color);
+    }
+
+    pub fn circle(&mut self, center: Vector2<f32>, radius: f32, color: [f32; 4]) {
+        self.circle_with_segments(center, radius, DEFAULT_CIRCLE_SEGMENTS, color);
+    }
+
+    pub fn circle_with_segments(&mut self, center: Vector2<f32>, radius: f32, segments: usize, color: [f32; 4]) {
+        let segments = segments.max(3);
+        let point = |index: usize| {
+            let angle = index as f32 / segments as f32 * 2.0 * PI;
+            center + Vector2::new(angle.cos(), angle.sin()) * radius
+        };
+        for index in 0..segments {
+            self.line(point(index), point(index + 1), color);
+        }
+    }
+
+    /// Connected line segments through the points, e.g. a path from the pathfinder
+    pub fn polyline(&mut self, points: &[Vector2<f32>], color: [f32; 4]) {
+        for pair in points.windows(2) {
+            self.line(pair[0], pair[1], color);
+        }
+    }
+
+    /// Drops everything queued this frame without drawing it
+    pub fn clear(&mut self) {
+        self.positions.clear();
+        self.colors.clear();
+    }
+
+    /// Draws the queue with the given projection and empties it. Needs a current GL context.
+    pub(crate) fn render(&mut self, projection_matrix: &Matrix4<f32>) {
+        if self.positions.is_empty() {
+            return;
+        }
+
+        if self.resources.is_none() {
+            match create_shader_program(DEBUG_VERTEX_SHADER, DEBUG_FRAGMENT_SHADER) {
+                Ok(shader_program) => {
+                    let position_vbo = VBO::new(&[]);
+                    let color_vbo = VBO::new(&[]);
+                    let mut vao = VAO::new();
+                    vao.setup_vertex_attributes(vec![(position_vbo.id(), 2, 0), (color_vbo.id(), 4, 1)], None);
+                    self.resources = Some(DebugDrawResources { shader_program, vao, position_vbo, color_vbo });
+                }
+                Err(e) => {
+                    log::error!("Debug draw shader failed, disabling debug draw: {}", e);
+                    self.set_enabled(false);
+                    return;
+                }
+            }
+        }
+        let Some(resources) = &mut self.resources else {
+            return;
+        };
+
+        resources.position_vbo.replace_data(&self.positions);
+        resources.color_vbo.replace_data(&self.colors);
+
+        unsafe {
+            gl::Disable(gl::DEPTH_TEST); // Always on top
+            gl::UseProgram(resources.shader_program);
+            let projection_location = gl::GetUniformLocation(resources.shader_program, CString::new("projection").unwrap().as_ptr());
+            gl::UniformMatrix4fv(projection_location, 1, gl::FALSE, projection_matrix.as_ptr());
+            resources.vao.bind();
+            gl::DrawArrays(gl::LINES, 0, (self.positions.len() / 2) as i32);
+            VAO::unbind();
+            gl::UseProgram(0);
+            gl::Enable(gl::DEPTH_TEST);
+        }
+        gl_check::check_gl_errors("debug draw");
+
+        self.clear();
+    }
+}
+
+impl Default for DebugDraw {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for DebugDraw {
+    fn drop(&mut self) {
+        if !gl_check::is_context_alive() {
+            return; // The context and everything in it is already gone
+        }
+        if let Some(resources) = &self.resources {
+            unsafe { gl::DeleteProgram(resources.shader_program); }
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use gl::types::GLenum;
+
+// Set while a GL context the framework's resources belong to exists, so Drop impls never call into a destroyed one
+static CONTEXT_ALIVE: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn set_context_alive(alive: bool) {
+    CONTEXT_ALIVE.store(alive, Ordering::Release);
+}
+
+/// Whether GL resources can still be deleted. False before a controller loads GL, in headless mode,
+/// and once the window has been dropped or FrameworkController::notify_context_lost was called.
+pub fn is_context_alive() -> bool {
+    CONTEXT_ALIVE.load(Ordering::Acquire)
+}
+
+/// With the "gl_debug" feature, logs every pending glGetError along with the operation and the call site.
+/// Without it this compiles to nothing, so it can be sprinkled after GL calls freely, including in game code.
+#[track_caller]
+#[inline]
+pub fn check_gl_errors(operation: &str) {
+    #[cfg(feature = "gl_debug")]
+    {
+        if !is_context_alive() {
+            return;
+        }
+        let location = std::panic::Location::caller();
+        loop {
+            let error = unsafe { gl::GetError() };
+            if error == gl::NO_ERROR {
+                break;
+            }
+            log::error!("GL error {} ({:#x}) after {} at {}:{}", error_name(error), error, operation, location.file(), location.line());
+        }
+    }
+    #[cfg(not(feature = "gl_debug"))]
+    let _ = operation;
+}
+
+#[cfg_attr(not(feature = "gl_debug"), allow(dead_code))]
+fn error_name(error: GLenum) -> &'static str {
+    match error {
+        gl::INVALID_ENUM => "GL_INVALID_ENUM",
+        gl::INVALID_VALUE => "GL_INVALID_VALUE",
+        gl::INVALID_OPERATION => "GL_INVALID_OPERATION",
+        gl::INVALID_FRAMEBUFFER_OPERATION => "GL_INVALID_FRAMEBUFFER_OPERATION",
+        gl::OUT_OF_MEMORY => "GL_OUT_OF_MEMORY",
+        gl::STACK_UNDERFLOW => "GL_STACK_UNDERFLOW",
+        gl::STACK_OVERFLOW => "GL_STACK_OVERFLOW",
+        _ => "unknown error",
+    }
+}
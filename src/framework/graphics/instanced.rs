@@ -0,0 +1,176 @@
+use std::{collections::HashMap, sync::{Arc, RwLock}};
+
+use gl::types::GLuint;
+use nalgebra::{Vector2, Vector3};
+
+use crate::framework::error::EngineResult;
+
+use super::{internal_object::graphics_object::Generic2DGraphicsObject, util::master_graphics_list::MasterGraphicsList};
+
+/// One copy of an instanced object's mesh
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Instance {
+    pub position: Vector2<f32>,
+    pub rotation: f32,
+    pub scale: f32,
+    pub color: [f32; 4], // Tint, for the shader to multiply in
+}
+
+impl Instance {
+    pub fn new(position: Vector2<f32>) -> Self {
+        Instance {
+            position,
+            rotation: 0.0,
+            scale: 1.0,
+            color: [1.0, 1.0, 1.0, 1.0],
+        }
+    }
+}
+
+/// Handle to an instance, stays valid until that instance is removed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InstanceId(u64);
+
+/// Draws many copies of one mesh (bullets, grass, coins) with a single glDrawArraysInstanced call. The mesh lives in the
+/// MasterGraphicsList as one instanced graphics object, so it keeps its layer and visibility like anything else,
+/// while instances are added, moved and removed here and uploaded together by sync.
+///
+/// The shader places each vertex with the per-instance attributes, for example
+/// `vec2 p = rotate(position * instanceTransform.w, instanceTransform.z) + instanceTransform.xy;`,
+/// see Generic2DGraphicsObject::set_instance_data for the layout.
+pub struct InstancedGraphicsObject {
+    name: String,
+    vertex_data: Vec<f32>,
+    texture_coords: Vec<f32>,
+    instances: Vec<Instance>, // Dense, so the upload is a straight copy
+    ids: Vec<InstanceId>, // Parallel to instances
+    indices: HashMap<InstanceId, usize>, // Id -> position in instances
+    next_id: u64,
+    dirty: bool, // Instances changed since the last sync
+}
+
+impl InstancedGraphicsObject {
+    pub fn new(name: &str, vertex_data: Vec<f32>, texture_coords: Vec<f32>) -> Self {
+        InstancedGraphicsObject {
+            name: name.to_owned(),
+            vertex_data,
+            texture_coords,
+            instances: Vec::new(),
+            ids: Vec::new(),
+            indices: HashMap::new(),
+            next_id: 0,
+            dirty: true,
+        }
+    }
+
+    /// Creates the instanced graphics object and adds it to the list. It sits at the origin, so instance positions are world positions.
+    pub fn add_to(&mut self, graphics_list: &MasterGraphicsList, shader_program: GLuint, texture_id: Option<GLuint>) -> EngineResult<String> {
+        let mut object = Generic2DGraphicsObject::new(
+            self.name.clone(),
+            self.vertex_data.clone(),
+            self.texture_coords.clone(),
+            shader_program,
+            Vector3::zeros(),
+            0.0,
+            1.0,
+            texture_id,
+            None,
+            None,
+        );
+        object.set_instance_data(self.build_instance_data());
+        object.set_always_render(true); // The mesh's bounds say nothing about where the instances are
+        self.dirty = false;
+
+        // The list may rename the object to avoid a collision
+        self.name = graphics_list.add_object(Arc::new(RwLock::new(object)))?;
+        Ok(self.name.clone())
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn add_instance(&mut self, instance: Instance) -> InstanceId {
+        let id = InstanceId(self.next_id);
+        self.next_id += 1;
+        self.indices.insert(id, self.instances.len());
+        self.instances.push(instance);
+        self.ids.push(id);
+        self.dirty = true;
+        id
+    }
+
+    /// Returns whether the instance existed
+    pub fn remove_instance(&mut self, id: InstanceId) -> bool {
+        let Some(index) = self.indices.remove(&id) else {
+            return false;
+        };
+        // Swap the last instance into the gap so the data stays dense
+        self.instances.swap_remove(index);
+        self.ids.swap_remove(index);
+        if let Some(moved_id) = self.ids.get(index) {
+            self.indices.insert(*moved_id, index);
+        }
+        self.dirty = true;
+        true
+    }
+
+    /// Replaces an instance's transform and color. Returns whether the instance exists.
+    pub fn update_instance(&mut self, id: InstanceId, instance: Instance) -> bool {
+        match self.indices.get(&id) {
+            Some(&index) => {
+                self.instances[index] = instance;
+                self.dirty = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn get_instance(&self, id: InstanceId) -> Option<Instance> {
+        self.indices.get(&id).map(|&index| self.instances[index])
+    }
+
+    /// Changes every instance in one pass, e.g. to move all bullets by their velocity
+    pub fn for_each_instance_mut<F: FnMut(InstanceId, &mut Instance)>(&mut self, mut visit: F) {
+        for (id, instance) in self.ids.iter().zip(self.instances.iter_mut()) {
+            visit(*id, instance);
+        }
+        self.dirty = true;
+    }
+
+    pub fn clear(&mut self) {
+        self.instances.clear();
+        self.ids.clear();
+        self.indices.clear();
+        self.dirty = true;
+    }
+
+    pub fn len(&self) -> usize {
+        self.instances.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instances.is_empty()
+    }
+
+    /// Uploads the instances to the graphics object if they changed. Call once per frame after updating them.
+    pub fn sync(&mut self, graphics_list: &MasterGraphicsList) {
+        if !self.dirty {
+            return;
+        }
+        if let Some(object) = graphics_list.get_object(&self.name) {
+            object.write().unwrap().set_instance_data(self.build_instance_data());
+            self.dirty = false;
+        }
+    }
+
+    fn build_instance_data(&self) -> Vec<f32> {
+        let mut instance_data = Vec::with_capacity(self.instances.len() * Generic2DGraphicsObject::INSTANCE_STRIDE);
+        for instance in &self.instances {
+            instance_data.extend_from_slice(&[instance.position.x, instance.position.y, instance.rotation, instance.scale]);
+            instance_data.extend_from_slice(&instance.color);
+        }
+        instance_data
+    }
+}
@@ -1,7 +1,11 @@
 pub mod custom_shader;
 pub mod graphics_object;
-mod vao;
-mod vbo;
+pub(crate) mod vao;
+pub(crate) mod vbo;
 pub mod animation_config;
 pub mod atlas_config;
-pub mod animation;
\ No newline at end of file
+pub mod animation;
+pub mod uniform;
+pub mod pivot;
+pub mod clip_rect;
+pub mod tiling_config;
@@ -0,0 +1,41 @@
+use nalgebra::{Matrix4, Point3};
+use serde::{Deserialize, Serialize};
+
+/// A rectangle drawing is limited to, e.g. so a scrollable list or a minimap doesn't spill over the rest of the screen.
+/// Objects get one with Generic2DGraphicsObject::set_clip_rect, custom drawing with MasterGraphicsList::push_clip_rect.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum ClipRect {
+    /// Pixels from the viewport's top-left corner, unaffected by the camera
+    Screen { x: f32, y: f32, width: f32, height: f32 },
+    /// A world-space box that moves and zooms with the camera like the objects inside it
+    World { min: [f32; 2], max: [f32; 2] },
+}
+
+impl ClipRect {
+    /// The scissor box (x, y, width, height in framebuffer pixels from the bottom left) for this rectangle
+    /// under the given projection and viewport
+    pub fn to_scissor(&self, projection_matrix: &Matrix4<f32>, viewport: (i32, i32, i32, i32)) -> (i32, i32, i32, i32) {
+        let (viewport_x, viewport_y, viewport_width, viewport_height) = (viewport.0 as f32, viewport.1 as f32, viewport.2 as f32, viewport.3 as f32);
+
+        let (left, bottom, right, top) = match self {
+            ClipRect::Screen { x, y, width, height } => {
+                // GL counts rows from the bottom
+                let top = viewport_y + viewport_height - y;
+                (viewport_x + x, top - height, viewport_x + x + width, top)
+            }
+            ClipRect::World { min, max } => {
+                let to_pixels = |x: f32, y: f32| {
+                    // Orthographic projection, so clip space is already normalized device coordinates
+                    let ndc = projection_matrix.transform_point(&Point3::new(x, y, 0.0));
+                    (viewport_x + (ndc.x + 1.0) / 2.0 * viewport_width, viewport_y + (ndc.y + 1.0) / 2.0 * viewport_height)
+                };
+                let (x0, y0) = to_pixels(min[0], min[1]);
+                let (x1, y1) = to_pixels(max[0], max[1]);
+                (x0.min(x1), y0.min(y1), x0.max(x1), y0.max(y1))
+            }
+        };
+
+        let (left, bottom) = (left.round() as i32, bottom.round() as i32);
+        (left, bottom, (right.round() as i32 - left).max(0), (top.round() as i32 - bottom).max(0))
+    }
+}
@@ -1,5 +1,5 @@
 use gl::types::GLuint;
-use crate::framework::graphics::compile::create_shader_program;
+use crate::framework::{error::EngineResult, graphics::compile::create_shader_program};
 
 pub struct CustomShader {
     shader_program: GLuint,
@@ -7,13 +7,13 @@ pub struct CustomShader {
 
 
 impl CustomShader {
-    pub fn new(vertex_shader_src: &str, fragment_shader_src: &str) -> Self {
-        let shader_program = create_shader_program(vertex_shader_src, fragment_shader_src);
+    pub fn new(vertex_shader_src: &str, fragment_shader_src: &str) -> EngineResult<Self> {
+        let shader_program = create_shader_program(vertex_shader_src, fragment_shader_src)?;
 
         let custom_shader = CustomShader {
             shader_program,
         };
-        custom_shader
+        Ok(custom_shader)
     }
 
     // Getter for shader_program
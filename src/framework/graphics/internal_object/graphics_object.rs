@@ -1,7 +1,9 @@
-use gl::types::GLuint;
-use nalgebra::{Matrix4, Vector3};
-use std::{ffi::CString, sync::{Arc, RwLock}};
-use super::{animation::{backward_animation, forward_animation, random_animation}, animation_config::AnimationConfig, atlas_config::AtlasConfig, vao::VAO, vbo::VBO};
+use gl::types::{GLenum, GLint, GLuint};
+use nalgebra::{Matrix4, Vector2, Vector3};
+use std::{any::{Any, TypeId}, collections::HashMap, ffi::CString, sync::{Arc, RwLock}};
+use crate::framework::graphics::{background_loader::UploadedMesh, gl_check};
+
+use super::{animation::{backward_animation, forward_animation, random_animation}, animation_config::AnimationConfig, atlas_config::AtlasConfig, clip_rect::ClipRect, pivot::Pivot, tiling_config::TilingConfig, uniform::UniformValue, vao::VAO, vbo::VBO};
 
 pub struct Generic2DGraphicsObject {
     name: String,
@@ -10,14 +12,36 @@ pub struct Generic2DGraphicsObject {
     vao: Arc<RwLock<VAO>>,
     position_vbo: Arc<VBO>, // VBO for positions
     tex_vbo: Arc<RwLock<VBO>>, // VBO for texture coordinates
+    vertex_colors: Vec<f32>, // Optional RGBA per vertex, fed to attribute location 2; empty leaves the attribute off
+    color_vbo: Option<Arc<RwLock<VBO>>>,
+    instanced: bool, // Drawn once per instance in instance_data rather than once
+    instance_data: Vec<f32>, // INSTANCE_STRIDE floats per instance, fed to attribute locations 3 and 4
+    instance_vbo: Option<Arc<RwLock<VBO>>>,
     shader_program: GLuint,
     position: nalgebra::Vector3<f32>,
     rotation: f32,
-    scale: f32,
+    scale: Vector2<f32>, // Per axis, so sprites can be squashed and stretched
+    pivot: Pivot, // Point rotation and scale happen around
     model_matrix: Matrix4<f32>,
     atlas_config: Option<AtlasConfig>,
     animation_config: Option<AnimationConfig>,
     elapsed_time: f32,
+    properties: HashMap<String, serde_json::Value>, // Arbitrary gameplay data (hp, loot table, door target...)
+    visible: bool, // Skipped when drawing if false
+    active: bool, // Skipped when updating (animation) and in spatial queries if false
+    lifetime: Option<f32>, // Seconds left before the object is despawned, None lives forever
+    despawn_offscreen: bool, // Despawn once the object leaves the view
+    entered_view: bool, // Has been inside the view since despawn_offscreen was set, so leaving it counts
+    always_render: bool, // Never skipped by view culling, for objects whose vertex data doesn't match what the shader draws
+    clip_rect: Option<ClipRect>, // Only drawn inside this rectangle
+    screen_space: bool, // Drawn in the overlay, ignoring the camera
+    tiling: Option<TilingConfig>, // Scales texture_coords on upload so the texture repeats
+    layer: i32, // Draw order, lower layers are drawn first (backgrounds low, UI high)
+    flip_x: bool, // Mirror horizontally, e.g. to face left with right-facing frames
+    flip_y: bool,
+    draw_mode: GLenum, // TRIANGLE_FAN for a single quad or polygon, TRIANGLES for meshes made of many quads (text, tilemaps)
+    user_data: HashMap<TypeId, Box<dyn Any + Send + Sync>>, // One value per type, for game state (health, AI...) attached to the object
+    uniforms: HashMap<String, (UniformValue, Option<GLint>)>, // Custom uniforms and their location, looked up on first draw
 }
 
 impl Clone for Generic2DGraphicsObject {
@@ -29,20 +53,44 @@ impl Clone for Generic2DGraphicsObject {
             vao: Arc::clone(&self.vao),
             position_vbo: Arc::clone(&self.position_vbo),
             tex_vbo: Arc::clone(&self.tex_vbo),
+            vertex_colors: self.vertex_colors.clone(),
+            color_vbo: self.color_vbo.clone(),
+            instanced: self.instanced,
+            instance_data: self.instance_data.clone(),
+            instance_vbo: self.instance_vbo.clone(),
             shader_program: self.shader_program,
             position: self.position,
             rotation: self.rotation,
             scale: self.scale,
+            pivot: self.pivot,
             model_matrix: self.model_matrix,
             atlas_config: self.atlas_config.clone(),
             animation_config: self.animation_config.clone(),
             elapsed_time: self.elapsed_time,
+            properties: self.properties.clone(),
+            visible: self.visible,
+            active: self.active,
+            lifetime: self.lifetime,
+            despawn_offscreen: self.despawn_offscreen,
+            entered_view: self.entered_view,
+            always_render: self.always_render,
+            clip_rect: self.clip_rect,
+            screen_space: self.screen_space,
+            tiling: self.tiling,
+            layer: self.layer,
+            flip_x: self.flip_x,
+            flip_y: self.flip_y,
+            draw_mode: self.draw_mode,
+            user_data: HashMap::new(), // Boxed user data can't be cloned, copies start without any
+            uniforms: self.uniforms.clone(),
         }
     }
 }
 
 impl Generic2DGraphicsObject {
     const FULL_ROTATION: f32 = 2.0 * std::f32::consts::PI; // 360 degrees in radians
+    /// Floats per instance: transform (x, y, rotation, scale) then color (r, g, b, a)
+    pub const INSTANCE_STRIDE: usize = 8;
 
     pub fn new(
         name: String,
@@ -56,27 +104,96 @@ impl Generic2DGraphicsObject {
         atlas_config: Option<AtlasConfig>,
         animation_config: Option<AnimationConfig>,
     ) -> Self {
-        let mut object = Self {
+        let mut object = Self::new_unbuffered(name, vertex_data, texture_coords, shader_program, position, rotation, scale, atlas_config, animation_config);
+        object.initialize(texture_id); // Pass texture ID to initialize
+        object
+    }
+
+    /// The object with a fresh VAO but no vertex buffers yet
+    #[allow(clippy::too_many_arguments)]
+    fn new_unbuffered(
+        name: String,
+        vertex_data: Vec<f32>,
+        texture_coords: Vec<f32>,
+        shader_program: GLuint,
+        position: Vector3<f32>,
+        rotation: f32,
+        scale: f32,
+        atlas_config: Option<AtlasConfig>,
+        animation_config: Option<AnimationConfig>,
+    ) -> Self {
+        Self {
             name,
             vertex_data,
             texture_coords,
             vao: Arc::new(RwLock::new(VAO::new())), // Create a new VAO wrapped in RwLock
-            position_vbo: Arc::new(VBO::new(&[])), // Placeholder for position VBO
-            tex_vbo: Arc::new(RwLock::new(VBO::new(&[]))), // Placeholder for texture VBO
+            position_vbo: Arc::new(VBO::empty()), // Placeholder for position VBO
+            tex_vbo: Arc::new(RwLock::new(VBO::empty())), // Placeholder for texture VBO
+            vertex_colors: Vec::new(),
+            color_vbo: None,
+            instanced: false,
+            instance_data: Vec::new(),
+            instance_vbo: None,
             shader_program,
             position,
             rotation,
-            scale,
+            scale: Vector2::new(scale, scale),
+            pivot: Pivot::Origin,
             model_matrix: Matrix4::identity(), // Identity matrix for 2D
             atlas_config,
             animation_config,
             elapsed_time: 0.0,
-        };
-        object.initialize(texture_id); // Pass texture ID to initialize
+            properties: HashMap::new(),
+            visible: true,
+            active: true,
+            lifetime: None,
+            despawn_offscreen: false,
+            entered_view: false,
+            always_render: false,
+            clip_rect: None,
+            screen_space: false,
+            tiling: None,
+            layer: 0,
+            flip_x: false,
+            flip_y: false,
+            draw_mode: gl::TRIANGLE_FAN,
+            user_data: HashMap::new(),
+            uniforms: HashMap::new(),
+        }
+    }
+
+    /// Builds an object around position and texture coordinate buffers uploaded elsewhere, e.g. by the BackgroundLoader.
+    /// Only the VAO, which can't be shared between contexts, is created here.
+    pub fn from_uploaded_mesh(
+        name: String,
+        mesh: UploadedMesh,
+        shader_program: GLuint,
+        position: Vector3<f32>,
+        rotation: f32,
+        scale: f32,
+        texture_id: Option<GLuint>,
+    ) -> Self {
+        let UploadedMesh { vertex_data, texture_coords, position_vbo, tex_vbo } = mesh;
+        let mut object = Self::new_unbuffered(name, vertex_data, texture_coords, shader_program, position, rotation, scale, None, None);
+        object.position_vbo = Arc::new(position_vbo);
+        object.tex_vbo = Arc::new(RwLock::new(tex_vbo));
+        object.setup_vao(texture_id);
         object
     }
 
     fn initialize(&mut self, texture_id: Option<GLuint>) {
+        // Initialize the VBOs with vertex data and texture coordinates
+        self.position_vbo = Arc::new(VBO::new(&self.vertex_data)); // Initialize position VBO
+        self.tex_vbo = Arc::new(RwLock::new(VBO::new(&self.get_uploaded_texture_coords()))); // Initialize texture VBO
+        self.setup_vao(texture_id);
+    }
+
+    /// Points the VAO at the position and texture coordinate VBOs, creating the color and instance VBOs as needed
+    fn setup_vao(&mut self, texture_id: Option<GLuint>) {
+        if !gl_check::is_context_alive() {
+            return; // Headless, the object only exists for game logic
+        }
+
         // Ensure the shader program is active before interacting with any attributes or uniforms
         unsafe {
             gl::UseProgram(self.shader_program);
@@ -86,15 +203,26 @@ impl Generic2DGraphicsObject {
         // Bind the VAO
         vao.bind();
 
-        // Initialize the VBOs with vertex data and texture coordinates
-        self.position_vbo = Arc::new(VBO::new(&self.vertex_data)); // Initialize position VBO
-        self.tex_vbo = Arc::new(RwLock::new(VBO::new(&self.texture_coords))); // Initialize texture VBO
-
-        // Setup vertex attributes for the VAO
-        vao.setup_vertex_attributes(vec![
+        let mut attributes = vec![
             (self.position_vbo.id(), 2, 0), // Position VBO
             (self.tex_vbo.read().unwrap().id(), 2, 1),       // Texture coordinate VBO
-        ], texture_id); // Pass texture ID dynamically
+        ];
+        self.color_vbo = None;
+        if !self.vertex_colors.is_empty() {
+            let color_vbo = VBO::new(&self.vertex_colors);
+            attributes.push((color_vbo.id(), 4, 2)); // Per-vertex color VBO
+            self.color_vbo = Some(Arc::new(RwLock::new(color_vbo)));
+        }
+
+        // Setup vertex attributes for the VAO
+        vao.setup_vertex_attributes(attributes, texture_id); // Pass texture ID dynamically
+
+        self.instance_vbo = None;
+        if self.instanced {
+            let instance_vbo = VBO::new(&self.instance_data);
+            vao.setup_instance_attributes(instance_vbo.id(), Self::INSTANCE_STRIDE, &[(3, 4, 0), (4, 4, 4)]);
+            self.instance_vbo = Some(Arc::new(RwLock::new(instance_vbo)));
+        }
 
         if let Some(atlas_config) = &self.atlas_config {
             self.initilize_animation_properties(&atlas_config);
@@ -102,43 +230,96 @@ impl Generic2DGraphicsObject {
 
         // Unbind the VAO
         VAO::unbind();
+        gl_check::check_gl_errors("vertex buffer setup");
     }
 
     // Apply translation, rotation, and scale as a combined transform
     pub fn update_model_matrix(&mut self) {
-        let translation_matrix = Matrix4::new_translation(&self.position);
+        let pivot = self.pivot.resolve(&self.vertex_data);
+        let translation_matrix = Matrix4::new_translation(&(self.position + Vector3::new(pivot.x, pivot.y, 0.0)));
         let rotation_matrix = Matrix4::new_rotation(Vector3::z() * self.rotation);
-        let scale_matrix = Matrix4::new_scaling(self.scale);
+        let (scale_x, scale_y) = self.get_signed_scale();
+        let scale_matrix = Matrix4::new_nonuniform_scaling(&Vector3::new(scale_x, scale_y, 1.0));
+        let pivot_matrix = Matrix4::new_translation(&Vector3::new(-pivot.x, -pivot.y, 0.0)); // Move the pivot to the origin first
 
-        self.model_matrix = translation_matrix * rotation_matrix * scale_matrix; // Combine transformations
+        self.model_matrix = translation_matrix * rotation_matrix * scale_matrix * pivot_matrix; // Combine transformations
     }
 
     pub fn apply_transform(&self, projection_matrix: &Matrix4<f32>) {
+        self.apply_transform_to(self.shader_program, projection_matrix);
+    }
+
+    fn apply_transform_to(&self, shader_program: GLuint, projection_matrix: &Matrix4<f32>) {
         unsafe {
             // Use the shader program
-            gl::UseProgram(self.shader_program);
+            gl::UseProgram(shader_program);
 
             // Set the projection matrix
-            let projection_location = gl::GetUniformLocation(self.shader_program, CString::new("projection").unwrap().as_ptr());
+            let projection_location = gl::GetUniformLocation(shader_program, CString::new("projection").unwrap().as_ptr());
             let projection_array: [f32; 16] = projection_matrix.as_slice().try_into().expect("Matrix conversion failed");
             gl::UniformMatrix4fv(projection_location, 1, gl::FALSE, projection_array.as_ptr());
 
             // Set the model matrix
-            let model_location = gl::GetUniformLocation(self.shader_program, CString::new("model").unwrap().as_ptr());
+            let model_location = gl::GetUniformLocation(shader_program, CString::new("model").unwrap().as_ptr());
             let model_array: [f32; 16] = self.model_matrix.as_slice().try_into().expect("Matrix conversion failed");
             gl::UniformMatrix4fv(model_location, 1, gl::FALSE, model_array.as_ptr());
         }
+        gl_check::check_gl_errors("transform uniforms");
+    }
+
+    /// Uploads the custom uniforms to the object's shader, which must be in use (apply_transform makes it so)
+    pub fn apply_uniforms(&mut self) {
+        for (name, (value, location)) in self.uniforms.iter_mut() {
+            let location = *location.get_or_insert_with(|| {
+                let found = unsafe { gl::GetUniformLocation(self.shader_program, CString::new(name.as_str()).unwrap_or_default().as_ptr()) };
+                if found == -1 {
+                    log::warn!("Uniform '{}' not found in shader program {}", name, self.shader_program);
+                }
+                found
+            });
+            if location != -1 {
+                value.apply(location);
+            }
+        }
+        gl_check::check_gl_errors("custom uniforms");
     }
 
     pub fn draw(&self) {
         unsafe {
             gl::UseProgram(self.shader_program);
+        }
+        self.draw_vertices();
+    }
+
+    /// Draws the object's geometry with another program in place of its own, e.g. a debug visualization.
+    /// The program gets the usual projection and model uniforms, plus `uniform int instanced` set to 1 for instanced objects.
+    pub(crate) fn draw_with_program(&self, shader_program: GLuint, projection_matrix: &Matrix4<f32>) {
+        self.apply_transform_to(shader_program, projection_matrix);
+        unsafe {
+            let instanced_location = gl::GetUniformLocation(shader_program, CString::new("instanced").unwrap().as_ptr());
+            if instanced_location != -1 {
+                gl::Uniform1i(instanced_location, self.instanced as GLint);
+            }
+        }
+        self.draw_vertices();
+    }
+
+    fn draw_vertices(&self) {
+        unsafe {
             let vao = self.vao.read().unwrap(); // Lock the RwLock for read access
             vao.bind();
             // Draw elements based on the number of vertices
-            gl::DrawArrays(gl::TRIANGLE_FAN, 0, (self.vertex_data.len() / 2) as i32);
+            if self.instanced {
+                let instance_count = self.get_instance_count();
+                if instance_count > 0 {
+                    gl::DrawArraysInstanced(self.draw_mode, 0, (self.vertex_data.len() / 2) as i32, instance_count as i32);
+                }
+            } else {
+                gl::DrawArrays(self.draw_mode, 0, (self.vertex_data.len() / 2) as i32);
+            }
             VAO::unbind();
         }
+        gl_check::check_gl_errors("draw");
     }
 
     // Method to calculate width and height based on vertex data
@@ -165,19 +346,74 @@ impl Generic2DGraphicsObject {
             .cloned()
             .fold(f32::NEG_INFINITY, f32::max);
         
-        let width = (max_x - min_x) * self.scale;
-        let height = (max_y - min_y) * self.scale;
+        let width = (max_x - min_x) * self.scale.x.abs();
+        let height = (max_y - min_y) * self.scale.y.abs();
         
         (width, height)
     }
 
+    /// World-space axis-aligned bounding box (min, max) of the vertex data after scale, rotation and translation
+    pub fn get_world_bounds(&self) -> (Vector3<f32>, Vector3<f32>) {
+        let (sin, cos) = self.rotation.sin_cos();
+        let mut min = Vector3::new(f32::INFINITY, f32::INFINITY, self.position.z);
+        let mut max = Vector3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, self.position.z);
+
+        let (scale_x, scale_y) = self.get_signed_scale();
+        let pivot = self.pivot.resolve(&self.vertex_data);
+        for vertex in self.vertex_data.chunks(2) {
+            let x = (vertex[0] - pivot.x) * scale_x;
+            let y = (vertex[1] - pivot.y) * scale_y;
+            let world_x = self.position.x + pivot.x + x * cos - y * sin;
+            let world_y = self.position.y + pivot.y + x * sin + y * cos;
+            min.x = min.x.min(world_x);
+            min.y = min.y.min(world_y);
+            max.x = max.x.max(world_x);
+            max.y = max.y.max(world_y);
+        }
+
+        (min, max)
+    }
+
+    /// Returns true if any part of the object's bounds falls inside the view described by the projection matrix
+    pub fn is_within_view(&self, projection_matrix: &Matrix4<f32>) -> bool {
+        let (min, max) = self.get_world_bounds();
+        let corners = [
+            Vector3::new(min.x, min.y, min.z),
+            Vector3::new(max.x, min.y, min.z),
+            Vector3::new(max.x, max.y, min.z),
+            Vector3::new(min.x, max.y, min.z),
+        ];
+
+        // Orthographic projection, so clip space is already normalized device coordinates
+        let mut ndc_min = Vector3::new(f32::INFINITY, f32::INFINITY, 0.0);
+        let mut ndc_max = Vector3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, 0.0);
+        for corner in corners {
+            let ndc = projection_matrix.transform_point(&corner.into());
+            ndc_min.x = ndc_min.x.min(ndc.x);
+            ndc_min.y = ndc_min.y.min(ndc.y);
+            ndc_max.x = ndc_max.x.max(ndc.x);
+            ndc_max.y = ndc_max.y.max(ndc.y);
+        }
+
+        ndc_max.x >= -1.0 && ndc_min.x <= 1.0 && ndc_max.y >= -1.0 && ndc_min.y <= 1.0
+    }
+
+    /// Counts down the lifetime, returns true once it has run out
+    pub fn update_lifetime(&mut self, delta_time: f32) -> bool {
+        if let Some(lifetime) = &mut self.lifetime {
+            *lifetime -= delta_time;
+            return *lifetime <= 0.0;
+        }
+        false
+    }
+
     pub fn initilize_animation_properties(&self, atlas_config: &AtlasConfig) {
         unsafe {    
             gl::UseProgram(self.shader_program);
             // Get the uniform location for number of columns in the atlas
             let atlas_columns_location = gl::GetUniformLocation(self.shader_program, CString::new("atlasColumns").unwrap().as_ptr());
             if atlas_columns_location == -1 {
-                println!("Error: uniform 'atlasColumns' not found in shader!");
+                log::warn!("Uniform 'atlasColumns' not found in shader program {}", self.shader_program);
             } else {
                 gl::Uniform1f(atlas_columns_location, atlas_config.atlas_columns as f32);
             }
@@ -185,7 +421,7 @@ impl Generic2DGraphicsObject {
             // Get the uniform location for number of rows in the atlas
             let atlas_rows_location = gl::GetUniformLocation(self.shader_program, CString::new("atlasRows").unwrap().as_ptr());
             if atlas_rows_location == -1 {
-                println!("Error: uniform 'atlasRows' not found in shader!");
+                log::warn!("Uniform 'atlasRows' not found in shader program {}", self.shader_program);
             } else {
                 gl::Uniform1f(atlas_rows_location, atlas_config.atlas_rows as f32);
             }
@@ -193,7 +429,7 @@ impl Generic2DGraphicsObject {
             // Get the uniform location for the columns_wide
             let columns_wide_location = gl::GetUniformLocation(self.shader_program, CString::new("columnsWide").unwrap().as_ptr());
             if columns_wide_location == -1 {
-                println!("Error: uniform 'columnsWide' not found in shader!");
+                log::warn!("Uniform 'columnsWide' not found in shader program {}", self.shader_program);
             } else {
                 gl::Uniform1f(columns_wide_location, atlas_config.columns_wide as f32);
             }
@@ -201,7 +437,7 @@ impl Generic2DGraphicsObject {
             // Get the uniform location for the rows_tall
             let rows_tall_location = gl::GetUniformLocation(self.shader_program, CString::new("rowsTall").unwrap().as_ptr());
             if rows_tall_location == -1 {
-                println!("Error: uniform 'rowsTall' not found in shader!");
+                log::warn!("Uniform 'rowsTall' not found in shader program {}", self.shader_program);
             } else {
                 gl::Uniform1f(rows_tall_location, atlas_config.rows_tall as f32);
             }
@@ -209,7 +445,7 @@ impl Generic2DGraphicsObject {
             // Get the uniform location for currentFrame
             let current_frame_location = gl::GetUniformLocation(self.shader_program, CString::new("currentFrame").unwrap().as_ptr());
             if current_frame_location == -1 {
-                println!("Error: uniform 'currentFrame' not found in shader!");
+                log::warn!("Uniform 'currentFrame' not found in shader program {}", self.shader_program);
             } else {
                 gl::Uniform1f(current_frame_location, atlas_config.current_frame as f32);
             }
@@ -248,22 +484,24 @@ impl Generic2DGraphicsObject {
     // Update texture coordinates based on the current frame, passing the raw data to the shader, making the GPU do the work.
     pub fn update_texture_coords_raw(&mut self) {
         if let Some(atlas_config) = &mut self.atlas_config {
+            if !gl_check::is_context_alive() {
+                return; // Headless, the frame advances but there is no shader to tell
+            }
 
             unsafe {
                 gl::UseProgram(self.shader_program);
                 // Get the uniform location for currentFrame
                 let current_frame_location = gl::GetUniformLocation(self.shader_program, CString::new("currentFrame").unwrap().as_ptr());
                 if current_frame_location == -1 {
-                    println!("Error: uniform 'currentFrame' not found in shader!");
+                    log::warn!("Uniform 'currentFrame' not found in shader program {}", self.shader_program);
                 } else {
                     gl::Uniform1f(current_frame_location, atlas_config.current_frame as f32);
                 }
             }
 
-            println!(
-                "Current Frame: {}", self.atlas_config.clone().unwrap().current_frame);
+            log::trace!("Current Frame: {}", atlas_config.current_frame);
 
-            self.update_texture_vbo(self.texture_coords.clone());
+            self.update_texture_vbo(self.get_uploaded_texture_coords());
         }
     }
     
@@ -310,7 +548,7 @@ impl Generic2DGraphicsObject {
     pub fn get_radius(&self) -> f32 {
         self.vertex_data
             .chunks(2)
-            .map(|v| (v[0].powi(2) + v[1].powi(2)).sqrt() * self.scale)
+            .map(|v| ((v[0] * self.scale.x).powi(2) + (v[1] * self.scale.y).powi(2)).sqrt())
             .fold(0.0, f32::max)
     }
 
@@ -318,6 +556,10 @@ impl Generic2DGraphicsObject {
         &self.name
     }
 
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
     pub fn get_atlas_config(&self) -> Option<AtlasConfig> {
         self.atlas_config.clone()
     }
@@ -334,6 +576,317 @@ impl Generic2DGraphicsObject {
         self.animation_config = animation_config;
     }
 
+    pub fn get_property(&self, key: &str) -> Option<&serde_json::Value> {
+        self.properties.get(key)
+    }
+
+    pub fn get_properties(&self) -> &HashMap<String, serde_json::Value> {
+        &self.properties
+    }
+
+    /// Sets a single property, returning the previous value if there was one
+    pub fn set_property(&mut self, key: &str, value: serde_json::Value) -> Option<serde_json::Value> {
+        self.properties.insert(key.to_owned(), value)
+    }
+
+    /// Replaces the whole property map, e.g. with the one read from a data file
+    pub fn set_properties(&mut self, properties: HashMap<String, serde_json::Value>) {
+        self.properties = properties;
+    }
+
+    pub fn remove_property(&mut self, key: &str) -> Option<serde_json::Value> {
+        self.properties.remove(key)
+    }
+
+    /// Attaches a value to the object, replacing and returning any previous value of the same type
+    pub fn insert_user_data<T: Any + Send + Sync>(&mut self, value: T) -> Option<T> {
+        self.user_data
+            .insert(TypeId::of::<T>(), Box::new(value))
+            .and_then(|previous| previous.downcast::<T>().ok())
+            .map(|previous| *previous)
+    }
+
+    pub fn get_user_data<T: Any + Send + Sync>(&self) -> Option<&T> {
+        self.user_data.get(&TypeId::of::<T>()).and_then(|value| value.downcast_ref::<T>())
+    }
+
+    pub fn get_user_data_mut<T: Any + Send + Sync>(&mut self) -> Option<&mut T> {
+        self.user_data.get_mut(&TypeId::of::<T>()).and_then(|value| value.downcast_mut::<T>())
+    }
+
+    pub fn remove_user_data<T: Any + Send + Sync>(&mut self) -> Option<T> {
+        self.user_data
+            .remove(&TypeId::of::<T>())
+            .and_then(|value| value.downcast::<T>().ok())
+            .map(|value| *value)
+    }
+
+    pub fn has_user_data<T: Any + Send + Sync>(&self) -> bool {
+        self.user_data.contains_key(&TypeId::of::<T>())
+    }
+
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn set_active(&mut self, active: bool) {
+        self.active = active;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn set_lifetime(&mut self, lifetime: Option<f32>) {
+        self.lifetime = lifetime;
+    }
+
+    pub fn get_lifetime(&self) -> Option<f32> {
+        self.lifetime
+    }
+
+    /// Removes the object once it leaves the view. Objects spawned outside it are kept until they have been on screen,
+    /// so something can enter from off-screen.
+    pub fn set_despawn_offscreen(&mut self, despawn_offscreen: bool) {
+        self.despawn_offscreen = despawn_offscreen;
+        self.entered_view = false;
+    }
+
+    pub fn get_despawn_offscreen(&self) -> bool {
+        self.despawn_offscreen
+    }
+
+    /// Whether despawn_offscreen should remove the object now: it was in the view before and has left it
+    pub fn has_left_view(&mut self, projection_matrix: &Matrix4<f32>) -> bool {
+        if !self.despawn_offscreen {
+            return false;
+        }
+        let in_view = self.is_within_view(projection_matrix);
+        self.entered_view |= in_view;
+        self.entered_view && !in_view
+    }
+
+    /// Opts the object out of view culling, e.g. for shader-positioned effects or fullscreen backgrounds
+    pub fn set_always_render(&mut self, always_render: bool) {
+        self.always_render = always_render;
+    }
+
+    pub fn get_always_render(&self) -> bool {
+        self.always_render
+    }
+
+    /// Limits drawing to a rectangle, e.g. for every item of a scrollable list. None draws the object everywhere again.
+    pub fn set_clip_rect(&mut self, clip_rect: Option<ClipRect>) {
+        self.clip_rect = clip_rect;
+    }
+
+    pub fn get_clip_rect(&self) -> Option<ClipRect> {
+        self.clip_rect
+    }
+
+    /// Moves the object into the screen-space overlay for HUD elements such as health bars and the score.
+    /// Overlay objects are drawn after the world with a fixed projection that the camera doesn't move or zoom,
+    /// so their position stays put on screen: x runs from -1 to 1 across the view, y is scaled by the aspect ratio.
+    pub fn set_screen_space(&mut self, screen_space: bool) {
+        self.screen_space = screen_space;
+    }
+
+    pub fn is_screen_space(&self) -> bool {
+        self.screen_space
+    }
+
+    /// Sets the draw order. Objects on higher layers are always drawn over lower ones, whatever their position.z.
+    /// Within a layer, a higher position.z is drawn on top, and objects with the same z are ordered by name.
+    pub fn set_layer(&mut self, layer: i32) {
+        self.layer = layer;
+    }
+
+    pub fn get_layer(&self) -> i32 {
+        self.layer
+    }
+
+    /// Mirrors the object around its local y axis, flipping the texture with it. Takes effect on the next draw.
+    pub fn set_flip_x(&mut self, flip_x: bool) {
+        self.flip_x = flip_x;
+    }
+
+    pub fn get_flip_x(&self) -> bool {
+        self.flip_x
+    }
+
+    /// Mirrors the object around its local x axis
+    pub fn set_flip_y(&mut self, flip_y: bool) {
+        self.flip_y = flip_y;
+    }
+
+    pub fn get_flip_y(&self) -> bool {
+        self.flip_y
+    }
+
+    /// Scale per axis with the flips applied as negative factors
+    fn get_signed_scale(&self) -> (f32, f32) {
+        let scale_x = if self.flip_x { -self.scale.x } else { self.scale.x };
+        let scale_y = if self.flip_y { -self.scale.y } else { self.scale.y };
+        (scale_x, scale_y)
+    }
+
+    /// How vertex_data is assembled into triangles. Defaults to gl::TRIANGLE_FAN, which draws one convex shape;
+    /// use gl::TRIANGLES for meshes built from several quads (six vertices each).
+    pub fn set_draw_mode(&mut self, draw_mode: GLenum) {
+        self.draw_mode = draw_mode;
+    }
+
+    pub fn get_draw_mode(&self) -> GLenum {
+        self.draw_mode
+    }
+
+    /// Replaces the mesh, e.g. when text changes
+    pub fn set_geometry(&mut self, vertex_data: Vec<f32>, texture_coords: Vec<f32>) {
+        self.vertex_data = vertex_data;
+        self.texture_coords = texture_coords;
+        self.rebuild_buffers();
+    }
+
+    /// Sets an RGBA color per vertex, passed to the shader as attribute location 2 (e.g. particle fades).
+    /// An empty list turns the attribute off again.
+    pub fn set_vertex_colors(&mut self, vertex_colors: Vec<f32>) {
+        self.vertex_colors = vertex_colors;
+        self.rebuild_buffers();
+    }
+
+    /// Replaces the mesh and its per-vertex colors in one upload
+    pub fn set_colored_geometry(&mut self, vertex_data: Vec<f32>, texture_coords: Vec<f32>, vertex_colors: Vec<f32>) {
+        self.vertex_data = vertex_data;
+        self.texture_coords = texture_coords;
+        self.vertex_colors = vertex_colors;
+        self.rebuild_buffers();
+    }
+
+    pub fn get_vertex_colors(&self) -> &[f32] {
+        &self.vertex_colors
+    }
+
+    /// Uploads the current vertex data. Buffers only this object uses are refilled in place; if they are shared
+    /// with a clone, or the color attribute is being switched on or off, a fresh VAO is built so clones keep their mesh.
+    fn rebuild_buffers(&mut self) {
+        let exclusive = Arc::strong_count(&self.vao) == 1
+            && Arc::strong_count(&self.position_vbo) == 1
+            && Arc::strong_count(&self.tex_vbo) == 1
+            && self.color_vbo.as_ref().is_none_or(|color_vbo| Arc::strong_count(color_vbo) == 1)
+            && self.instance_vbo.as_ref().is_none_or(|instance_vbo| Arc::strong_count(instance_vbo) == 1);
+        let layout_unchanged = self.color_vbo.is_some() != self.vertex_colors.is_empty() && self.instance_vbo.is_some() == self.instanced;
+
+        if let (true, true, Some(position_vbo)) = (exclusive, layout_unchanged, Arc::get_mut(&mut self.position_vbo)) {
+            position_vbo.replace_data(&self.vertex_data);
+            self.tex_vbo.write().unwrap().replace_data(&self.get_uploaded_texture_coords());
+            if let Some(color_vbo) = &self.color_vbo {
+                color_vbo.write().unwrap().replace_data(&self.vertex_colors);
+            }
+            if let Some(instance_vbo) = &self.instance_vbo {
+                instance_vbo.write().unwrap().replace_data(&self.instance_data);
+            }
+            return;
+        }
+
+        let texture_id = self.vao.read().unwrap().texture_id();
+        self.vao = Arc::new(RwLock::new(VAO::new()));
+        self.initialize(texture_id);
+    }
+
+    /// Turns the object into an instanced one, drawn once per instance with a single draw call, and sets the instances.
+    /// Each instance is INSTANCE_STRIDE floats; the shader reads them as `layout(location = 3) in vec4 instanceTransform;`
+    /// (x, y, rotation, scale) and `layout(location = 4) in vec4 instanceColor;`. An instanced object with no instances draws nothing.
+    pub fn set_instance_data(&mut self, instance_data: Vec<f32>) {
+        self.instance_data = instance_data;
+        match &self.instance_vbo {
+            Some(instance_vbo) if self.instanced && Arc::strong_count(instance_vbo) == 1 => {
+                instance_vbo.write().unwrap().replace_data(&self.instance_data);
+            }
+            _ => {
+                self.instanced = true;
+                self.rebuild_buffers();
+            }
+        }
+    }
+
+    pub fn is_instanced(&self) -> bool {
+        self.instanced
+    }
+
+    pub fn get_instance_count(&self) -> usize {
+        self.instance_data.len() / Self::INSTANCE_STRIDE
+    }
+
+    pub fn get_vertex_data(&self) -> &[f32] {
+        &self.vertex_data
+    }
+
+    pub fn get_texture_coords(&self) -> &[f32] {
+        &self.texture_coords
+    }
+
+    /// Repeats the texture across the object by scaling its texture coordinates, or stretches it once again with None.
+    /// Meant for whole textures: a repeating atlas frame would wrap into its neighbours.
+    pub fn set_tiling(&mut self, tiling: Option<TilingConfig>) {
+        self.tiling = tiling;
+        self.rebuild_buffers();
+    }
+
+    pub fn get_tiling(&self) -> Option<TilingConfig> {
+        self.tiling
+    }
+
+    /// The texture coordinates as they go to the GPU, with tiling applied
+    fn get_uploaded_texture_coords(&self) -> Vec<f32> {
+        match self.tiling {
+            Some(tiling) => self.texture_coords.chunks_exact(2).flat_map(|uv| [uv[0] * tiling.horizontal_scalar, uv[1] * tiling.vertical_scalar]).collect(),
+            None => self.texture_coords.clone(),
+        }
+    }
+
+    /// Sets a custom uniform that is uploaded every time the object is drawn, e.g. time, health or a flash color
+    pub fn set_uniform(&mut self, name: &str, value: UniformValue) {
+        match self.uniforms.get_mut(name) {
+            Some((existing, _)) => *existing = value, // Keep the cached location
+            None => {
+                self.uniforms.insert(name.to_owned(), (value, None));
+            }
+        }
+    }
+
+    pub fn set_uniform_f32(&mut self, name: &str, value: f32) {
+        self.set_uniform(name, UniformValue::F32(value));
+    }
+
+    pub fn set_uniform_vec2(&mut self, name: &str, value: [f32; 2]) {
+        self.set_uniform(name, UniformValue::Vec2(value));
+    }
+
+    pub fn set_uniform_vec4(&mut self, name: &str, value: [f32; 4]) {
+        self.set_uniform(name, UniformValue::Vec4(value));
+    }
+
+    pub fn set_uniform_mat4(&mut self, name: &str, value: Matrix4<f32>) {
+        self.set_uniform(name, UniformValue::Mat4(value));
+    }
+
+    pub fn set_uniform_i32(&mut self, name: &str, value: i32) {
+        self.set_uniform(name, UniformValue::I32(value));
+    }
+
+    pub fn get_uniform(&self, name: &str) -> Option<UniformValue> {
+        self.uniforms.get(name).map(|(value, _)| *value)
+    }
+
+    /// Stops uploading a custom uniform. The shader keeps the last value it was given.
+    pub fn remove_uniform(&mut self, name: &str) -> Option<UniformValue> {
+        self.uniforms.remove(name).map(|(value, _)| value)
+    }
+
     pub fn set_position(&mut self, position: nalgebra::Vector3<f32>) {
         self.position = position;
     }
@@ -342,8 +895,23 @@ impl Generic2DGraphicsObject {
         self.rotation = rotation % Self::FULL_ROTATION;
     }
 
+    /// Sets the point rotation and scale happen around. The pivot keeps its place in the world, so the rest of the object
+    /// turns and grows around it; an unrotated, unscaled object doesn't move when its pivot changes.
+    pub fn set_pivot(&mut self, pivot: Pivot) {
+        self.pivot = pivot;
+    }
+
+    pub fn get_pivot(&self) -> Pivot {
+        self.pivot
+    }
+
+    /// Scales both axes by the same factor
     pub fn set_scale(&mut self, scale: f32) {
-        self.scale = scale;
+        self.scale = Vector2::new(scale, scale);
+    }
+
+    pub fn set_scale_xy(&mut self, scale_x: f32, scale_y: f32) {
+        self.scale = Vector2::new(scale_x, scale_y);
     }
 
     pub fn get_model_matrix(&self) -> Matrix4<f32> {
@@ -358,7 +926,7 @@ impl Generic2DGraphicsObject {
         self.rotation
     }
 
-    pub fn get_scale(&self) -> f32 {
+    pub fn get_scale(&self) -> Vector2<f32> {
         self.scale
     }
 
@@ -370,9 +938,12 @@ impl Generic2DGraphicsObject {
         println!("Shader Program: {}", self.shader_program);
         println!("Position: {:?}", self.position);
         println!("Rotation: {}", self.rotation);
-        println!("Scale: {}", self.scale);
+        println!("Scale: ({}, {})", self.scale.x, self.scale.y);
+        println!("Visible: {}, Active: {}, Layer: {}", self.visible, self.active, self.layer);
+        println!("Flip X: {}, Flip Y: {}, Pivot: {:?}", self.flip_x, self.flip_y, self.pivot);
         println!("Model Matrix: {:?}", self.model_matrix);
         println!("Position VBO ID: {}", self.position_vbo.id());
-        println!("Texture VBO ID: {}\n", self.tex_vbo.read().unwrap().id());
+        println!("Texture VBO ID: {}", self.tex_vbo.read().unwrap().id());
+        println!("Properties: {:?}\n", self.properties);
     }
 }
\ No newline at end of file
@@ -0,0 +1,54 @@
+use nalgebra::Vector2;
+use serde::{Deserialize, Serialize};
+
+/// The point of an object's vertex data that rotation and scaling happen around. It stays put in the world while
+/// the object turns or grows, e.g. BottomCenter to rotate a character around its feet.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum Pivot {
+    /// The vertex data's (0, 0), the behaviour before pivots existed
+    #[default]
+    Origin,
+    /// Center of the vertex data's bounding box
+    Center,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    BottomCenter,
+    /// An arbitrary point in vertex-data units
+    Offset(f32, f32),
+}
+
+impl Pivot {
+    /// Where the pivot sits in the given vertex data (x, y pairs), before scale and rotation
+    pub fn resolve(&self, vertex_data: &[f32]) -> Vector2<f32> {
+        match self {
+            Pivot::Origin => return Vector2::zeros(),
+            Pivot::Offset(x, y) => return Vector2::new(*x, *y),
+            _ => {}
+        }
+        if vertex_data.len() < 2 {
+            return Vector2::zeros();
+        }
+
+        let mut min = Vector2::new(f32::INFINITY, f32::INFINITY);
+        let mut max = Vector2::new(f32::NEG_INFINITY, f32::NEG_INFINITY);
+        for vertex in vertex_data.chunks_exact(2) {
+            min.x = min.x.min(vertex[0]);
+            min.y = min.y.min(vertex[1]);
+            max.x = max.x.max(vertex[0]);
+            max.y = max.y.max(vertex[1]);
+        }
+        let center = (min + max) / 2.0;
+
+        match self {
+            Pivot::Center => center,
+            Pivot::TopLeft => Vector2::new(min.x, max.y),
+            Pivot::TopRight => max,
+            Pivot::BottomLeft => min,
+            Pivot::BottomRight => Vector2::new(max.x, min.y),
+            Pivot::BottomCenter => Vector2::new(center.x, min.y),
+            Pivot::Origin | Pivot::Offset(..) => unreachable!(), // Handled above
+        }
+    }
+}
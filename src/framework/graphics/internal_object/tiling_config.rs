@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// How many times a texture repeats across an object, e.g. a large background tiled from a small texture.
+/// Textures from the TextureManager wrap with GL_REPEAT, so scaled texture coordinates simply wrap around.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct TilingConfig {
+    pub horizontal_scalar: f32, // Repeats along the texture's u axis
+    pub vertical_scalar: f32, // Repeats along the texture's v axis
+}
+
+impl Default for TilingConfig {
+    fn default() -> Self {
+        TilingConfig {
+            horizontal_scalar: 1.0,
+            vertical_scalar: 1.0,
+        }
+    }
+}
+
+impl TilingConfig {
+    pub fn new(horizontal_scalar: f32, vertical_scalar: f32) -> Self {
+        TilingConfig { horizontal_scalar, vertical_scalar }
+    }
+
+    /// Repeats that give each tile the given size on an object of the given size, both in world units
+    pub fn for_size(object_width: f32, object_height: f32, tile_width: f32, tile_height: f32) -> Self {
+        TilingConfig {
+            horizontal_scalar: object_width / tile_width.max(f32::EPSILON),
+            vertical_scalar: object_height / tile_height.max(f32::EPSILON),
+        }
+    }
+}
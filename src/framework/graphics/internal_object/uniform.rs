@@ -0,0 +1,27 @@
+use gl::types::GLint;
+use nalgebra::Matrix4;
+
+/// A value for a custom shader uniform, see Generic2DGraphicsObject::set_uniform
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UniformValue {
+    F32(f32),
+    Vec2([f32; 2]),
+    Vec4([f32; 4]),
+    Mat4(Matrix4<f32>),
+    I32(i32), // Also used for sampler units
+}
+
+impl UniformValue {
+    /// Uploads the value to a location in the program currently in use
+    pub(crate) fn apply(&self, location: GLint) {
+        unsafe {
+            match self {
+                UniformValue::F32(value) => gl::Uniform1f(location, *value),
+                UniformValue::Vec2([x, y]) => gl::Uniform2f(location, *x, *y),
+                UniformValue::Vec4([x, y, z, w]) => gl::Uniform4f(location, *x, *y, *z, *w),
+                UniformValue::Mat4(matrix) => gl::UniformMatrix4fv(location, 1, gl::FALSE, matrix.as_ptr()),
+                UniformValue::I32(value) => gl::Uniform1i(location, *value),
+            }
+        }
+    }
+}
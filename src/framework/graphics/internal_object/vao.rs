@@ -1,4 +1,6 @@
-use gl::types::{GLint, GLuint};
+use gl::types::{GLint, GLsizei, GLuint};
+
+use crate::framework::graphics::gl_check;
 
 pub struct VAO {
     id: GLuint, // Stores the VAO ID generated by OpenGL
@@ -6,12 +8,14 @@ pub struct VAO {
 }
 
 impl VAO {
-    /// Creates a new Vertex Array Object.
+    /// Creates a new Vertex Array Object. Without a GL context (headless) it is left as 0 and nothing is allocated.
     pub fn new() -> Self {
         let mut vao: GLuint = 0;
 
-        unsafe {
-            gl::GenVertexArrays(1, &mut vao);
+        if gl_check::is_context_alive() {
+            unsafe {
+                gl::GenVertexArrays(1, &mut vao);
+            }
         }
 
         Self {
@@ -38,6 +42,10 @@ impl VAO {
         }
     }
 
+    pub fn texture_id(&self) -> Option<GLuint> {
+        self.texture_id
+    }
+
     pub fn setup_vertex_attributes(&mut self, vbo_ids: Vec<(GLuint, GLint, GLuint)>, texture_id: Option<GLuint>) {
         self.texture_id = texture_id; // Store the texture ID
 
@@ -57,11 +65,38 @@ impl VAO {
         }
         VAO::unbind();
     }
+
+    /// Points attributes at interleaved per-instance data in one VBO, advancing once per instance instead of per vertex.
+    /// Attributes are (index, size, offset), with the stride and offsets counted in floats.
+    pub fn setup_instance_attributes(&mut self, vbo_id: GLuint, stride: usize, attributes: &[(GLuint, GLint, usize)]) {
+        self.bind();
+
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo_id);
+            for &(index, size, offset) in attributes {
+                gl::VertexAttribPointer(
+                    index,
+                    size,
+                    gl::FLOAT,
+                    gl::FALSE,
+                    (stride * std::mem::size_of::<f32>()) as GLsizei,
+                    (offset * std::mem::size_of::<f32>()) as *const std::ffi::c_void,
+                );
+                gl::EnableVertexAttribArray(index);
+                gl::VertexAttribDivisor(index, 1);
+            }
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+        }
+        VAO::unbind();
+    }
 }
 
 impl Drop for VAO {
     /// Clean up the VAO when it's no longer needed (automatically called by Rust).
     fn drop(&mut self) {
+        if !gl_check::is_context_alive() {
+            return; // The context and everything in it is already gone
+        }
         unsafe {
             gl::DeleteVertexArrays(1, &self.id);
         }
@@ -2,13 +2,19 @@ extern crate gl;
 use std::mem;
 use gl::types::*;
 
+use crate::framework::graphics::gl_check;
+
 pub struct VBO {
     id: GLuint, // Stores the VBO ID generated by OpenGL
 }
 
 impl VBO {
     /// Creates a new Vertex Buffer Object and uploads the provided vertex data.
+    /// Without a GL context (headless) this is an empty placeholder instead.
     pub fn new(data: &[f32]) -> Self {
+        if !gl_check::is_context_alive() {
+            return Self::empty();
+        }
         let mut vbo: GLuint = 0;
 
         unsafe {
@@ -35,8 +41,18 @@ impl VBO {
         }
     }
 
+    /// A placeholder that owns no buffer, for objects whose real buffers are created or handed over later
+    pub fn empty() -> Self {
+        Self {
+            id: 0, // Deleting buffer 0 is ignored by GL
+        }
+    }
+
     /// Updates the data in the VBO with new vertex data.
     pub fn update_data(&mut self, data: &[f32]) {
+        if !gl_check::is_context_alive() {
+            return;
+        }
         unsafe {
             // Bind the buffer to update its contents
             gl::BindBuffer(gl::ARRAY_BUFFER, self.id);
@@ -54,6 +70,23 @@ impl VBO {
         }
     }
 
+    /// Reallocates the buffer with new data of any size. The buffer ID stays the same, so VAOs pointing at it stay valid.
+    pub fn replace_data(&mut self, data: &[f32]) {
+        if !gl_check::is_context_alive() {
+            return;
+        }
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.id);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                mem::size_of_val(data) as GLsizeiptr,
+                data.as_ptr() as *const GLvoid,
+                gl::DYNAMIC_DRAW, // Meshes that get replaced tend to be replaced often (particles, text)
+            );
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+        }
+    }
+
     /// Returns the VBO ID.
     pub fn id(&self) -> GLuint {
         self.id
@@ -63,6 +96,9 @@ impl VBO {
 impl Drop for VBO {
     /// Clean up the buffer when it's no longer needed (automatically called by Rust).
     fn drop(&mut self) {
+        if !gl_check::is_context_alive() {
+            return; // The context and everything in it is already gone
+        }
         unsafe {
             gl::DeleteBuffers(1, &self.id);
         }
@@ -0,0 +1,161 @@
+use std::sync::{Arc, RwLock};
+
+use gl::types::GLuint;
+use nalgebra::{Vector2, Vector3};
+use serde::{Deserialize, Serialize};
+
+use crate::framework::error::EngineResult;
+
+use super::{internal_object::graphics_object::Generic2DGraphicsObject, util::master_graphics_list::MasterGraphicsList};
+
+/// How a texture region is cut into a 3x3 grid. Corners keep their size, edges stretch along one axis and the center along both.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct NineSliceConfig {
+    pub region: [f32; 4], // u_min, v_min, u_max, v_max of the whole panel in the texture
+    pub borders_uv: [f32; 4], // left, right, top, bottom border thickness in UV units
+    pub borders: [f32; 4], // left, right, top, bottom border thickness in world units
+}
+
+impl Default for NineSliceConfig {
+    fn default() -> Self {
+        NineSliceConfig {
+            region: [0.0, 0.0, 1.0, 1.0],
+            borders_uv: [0.25, 0.25, 0.25, 0.25],
+            borders: [0.1, 0.1, 0.1, 0.1],
+        }
+    }
+}
+
+/// A scalable panel drawn from one texture region as nine quads, so its corners don't stretch when it is resized.
+/// Resize it with set_size, or scale its graphics object like any other and call sync: either way the borders keep
+/// their configured world size and only the edges and center stretch.
+pub struct NineSliceObject {
+    name: String,
+    config: NineSliceConfig,
+    width: f32, // World units before the object's scale
+    height: f32,
+    baked_scale: Vector2<f32>, // The object's scale the current mesh compensates for
+}
+
+impl NineSliceObject {
+    pub fn new(name: &str, config: NineSliceConfig, width: f32, height: f32) -> Self {
+        NineSliceObject {
+            name: name.to_owned(),
+            config,
+            width,
+            height,
+            baked_scale: Vector2::new(1.0, 1.0),
+        }
+    }
+
+    /// Creates the graphics object, centered on position, and adds it to the list
+    pub fn add_to(&mut self, graphics_list: &MasterGraphicsList, shader_program: GLuint, texture_id: GLuint, position: Vector3<f32>) -> EngineResult<String> {
+        self.baked_scale = Vector2::new(1.0, 1.0); // New objects start unscaled
+        let (vertices, texture_coords) = self.build_mesh();
+        let mut object = Generic2DGraphicsObject::new(
+            self.name.clone(),
+            vertices,
+            texture_coords,
+            shader_program,
+            position,
+            0.0,
+            1.0,
+            Some(texture_id),
+            None,
+            None,
+        );
+        object.set_draw_mode(gl::TRIANGLES);
+
+        // The list may rename the object to avoid a collision
+        self.name = graphics_list.add_object(Arc::new(RwLock::new(object)))?;
+        Ok(self.name.clone())
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_size(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    /// Scales the graphics object and rebuilds the mesh for it in one go
+    pub fn set_scale(&mut self, scale_x: f32, scale_y: f32, graphics_list: &MasterGraphicsList) {
+        if let Some(object) = graphics_list.get_object(&self.name) {
+            object.write().unwrap().set_scale_xy(scale_x, scale_y);
+        }
+        self.rebuild(graphics_list);
+    }
+
+    /// Rebuilds the mesh if the graphics object's scale changed since the last build. Call after scaling it, or once per frame.
+    pub fn sync(&mut self, graphics_list: &MasterGraphicsList) {
+        let Some(object) = graphics_list.get_object(&self.name) else {
+            return;
+        };
+        let scale = object.read().unwrap().get_scale();
+        if scale != self.baked_scale {
+            self.rebuild(graphics_list);
+        }
+    }
+
+    /// Resizes the panel, keeping the borders at their configured thickness
+    pub fn set_size(&mut self, width: f32, height: f32, graphics_list: &MasterGraphicsList) {
+        self.width = width;
+        self.height = height;
+        self.rebuild(graphics_list);
+    }
+
+    pub fn get_config(&self) -> NineSliceConfig {
+        self.config
+    }
+
+    pub fn set_config(&mut self, config: NineSliceConfig, graphics_list: &MasterGraphicsList) {
+        self.config = config;
+        self.rebuild(graphics_list);
+    }
+
+    fn rebuild(&mut self, graphics_list: &MasterGraphicsList) {
+        if let Some(object) = graphics_list.get_object(&self.name) {
+            let mut object = object.write().unwrap();
+            self.baked_scale = object.get_scale();
+            let (vertices, texture_coords) = self.build_mesh();
+            object.set_geometry(vertices, texture_coords);
+        }
+    }
+
+    /// Nine quads as gl::TRIANGLES, centered on the origin. Borders are divided by the object's scale,
+    /// so once the object is scaled they come out at their configured world size.
+    pub fn build_mesh(&self) -> (Vec<f32>, Vec<f32>) {
+        let scale_x = self.baked_scale.x.abs().max(f32::EPSILON);
+        let scale_y = self.baked_scale.y.abs().max(f32::EPSILON);
+        let [left, right, top, bottom] = self.config.borders;
+        // A panel smaller than its borders shrinks them proportionally instead of folding over
+        let horizontal_fit = (self.width * scale_x / (left + right)).min(1.0);
+        let vertical_fit = (self.height * scale_y / (top + bottom)).min(1.0);
+        let (left, right) = (left * horizontal_fit / scale_x, right * horizontal_fit / scale_x);
+        let (top, bottom) = (top * vertical_fit / scale_y, bottom * vertical_fit / scale_y);
+
+        let half_width = self.width / 2.0;
+        let half_height = self.height / 2.0;
+        let xs = [-half_width, -half_width + left, half_width - right, half_width];
+        let ys = [half_height, half_height - top, -half_height + bottom, -half_height]; // Top to bottom
+
+        let [u_min, v_min, u_max, v_max] = self.config.region;
+        let [left_uv, right_uv, top_uv, bottom_uv] = self.config.borders_uv;
+        let us = [u_min, u_min + left_uv, u_max - right_uv, u_max];
+        let vs = [v_max, v_max - top_uv, v_min + bottom_uv, v_min]; // Top to bottom, texture v counts up
+
+        let mut vertices = Vec::with_capacity(9 * 12);
+        let mut texture_coords = Vec::with_capacity(9 * 12);
+        for row in 0..3 {
+            for column in 0..3 {
+                let (x0, x1, y0, y1) = (xs[column], xs[column + 1], ys[row], ys[row + 1]);
+                let (u0, u1, v0, v1) = (us[column], us[column + 1], vs[row], vs[row + 1]);
+                vertices.extend_from_slice(&[x0, y0, x0, y1, x1, y1, x0, y0, x1, y1, x1, y0]);
+                texture_coords.extend_from_slice(&[u0, v0, u0, v1, u1, v1, u0, v0, u1, v1, u1, v0]);
+            }
+        }
+
+        (vertices, texture_coords)
+    }
+}
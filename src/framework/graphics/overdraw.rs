@@ -0,0 +1,31 @@
+// Replaces every object's shader in RenderMode::Overdraw. Each fragment adds the same small amount of color,
+// so with additive blending the brightness of a pixel shows how many times it was filled.
+
+pub(crate) const OVERDRAW_VERTEX_SHADER: &str = r#"
+#version 330 core
+layout(location = 0) in vec2 position;
+layout(location = 3) in vec4 instanceTransform; // x, y, rotation, scale, only bound for instanced objects
+
+uniform mat4 projection;
+uniform mat4 model;
+uniform int instanced;
+
+void main() {
+    vec2 p = position;
+    if (instanced == 1) {
+        float c = cos(instanceTransform.z);
+        float s = sin(instanceTransform.z);
+        p = mat2(c, s, -s, c) * (p * instanceTransform.w) + instanceTransform.xy;
+    }
+    gl_Position = projection * model * vec4(p, 0.0, 1.0);
+}
+"#;
+
+pub(crate) const OVERDRAW_FRAGMENT_SHADER: &str = r#"
+#version 330 core
+out vec4 FragColor;
+
+void main() {
+    FragColor = vec4(0.12, 0.06, 0.02, 1.0); // Saturates to white after about eight layers
+}
+"#;
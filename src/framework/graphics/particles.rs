@@ -0,0 +1,228 @@
+use std::{f32::consts::PI, sync::{Arc, RwLock}};
+
+use gl::types::GLuint;
+use nalgebra::{Vector2, Vector3};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::framework::error::EngineResult;
+
+use super::{internal_object::graphics_object::Generic2DGraphicsObject, util::master_graphics_list::MasterGraphicsList};
+
+/// How an emitter spawns and animates its particles. Every field has a default, so scene or config JSON
+/// only needs the ones it changes.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ParticleConfig {
+    pub emission_rate: f32, // Particles per second, 0 to only emit through burst
+    pub max_particles: usize, // Emission pauses while this many are alive
+    pub lifetime_min: f32, // Seconds
+    pub lifetime_max: f32,
+    pub speed_min: f32, // World units per second
+    pub speed_max: f32,
+    pub direction: f32, // Radians, 0 points along +x
+    pub spread: f32, // Radians either side of direction, PI for every direction
+    pub gravity: [f32; 2], // Acceleration applied to every particle
+    pub start_size: f32, // Quad width/height in world units at birth
+    pub end_size: f32, // ...and at death, linearly in between
+    pub start_color: [f32; 4], // RGBA at birth
+    pub end_color: [f32; 4], // RGBA at death, set alpha to 0 for a fade out
+}
+
+impl Default for ParticleConfig {
+    fn default() -> Self {
+        ParticleConfig {
+            emission_rate: 20.0,
+            max_particles: 500,
+            lifetime_min: 0.5,
+            lifetime_max: 1.0,
+            speed_min: 0.1,
+            speed_max: 0.3,
+            direction: PI / 2.0,
+            spread: PI,
+            gravity: [0.0, 0.0],
+            start_size: 0.05,
+            end_size: 0.0,
+            start_color: [1.0, 1.0, 1.0, 1.0],
+            end_color: [1.0, 1.0, 1.0, 0.0],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Particle {
+    position: Vector2<f32>,
+    velocity: Vector2<f32>,
+    age: f32,
+    lifetime: f32,
+}
+
+/// Spawns and simulates many short-lived quads and draws them all as one graphics object, so a thousand particles
+/// cost one draw call. Particles live in world space: moving the emitter leaves already-emitted particles behind.
+///
+/// The object's shader receives an RGBA color per vertex at attribute location 2 (`layout(location = 2) in vec4 color;`)
+/// and should multiply it into its output to get fading.
+pub struct ParticleEmitter {
+    name: String,
+    config: ParticleConfig,
+    position: Vector2<f32>,
+    particles: Vec<Particle>,
+    emission_accumulator: f32, // Fractional particles carried over between frames
+    enabled: bool,
+}
+
+impl ParticleEmitter {
+    pub fn new(name: &str, config: ParticleConfig) -> Self {
+        ParticleEmitter {
+            name: name.to_owned(),
+            config,
+            position: Vector2::zeros(),
+            particles: Vec::new(),
+            emission_accumulator: 0.0,
+            enabled: true,
+        }
+    }
+
+    /// Creates the batched graphics object and adds it to the list. The object sits at the origin,
+    /// the emitter itself is moved with set_position.
+    pub fn add_to(&mut self, graphics_list: &MasterGraphicsList, shader_program: GLuint, texture_id: Option<GLuint>) -> EngineResult<String> {
+        let mut object = Generic2DGraphicsObject::new(
+            self.name.clone(),
+            Vec::new(),
+            Vec::new(),
+            shader_program,
+            Vector3::zeros(),
+            0.0,
+            1.0,
+            texture_id,
+            None,
+            None,
+        );
+        object.set_draw_mode(gl::TRIANGLES);
+
+        // The list may rename the object to avoid a collision
+        self.name = graphics_list.add_object(Arc::new(RwLock::new(object)))?;
+        Ok(self.name.clone())
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_config(&self) -> &ParticleConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: ParticleConfig) {
+        self.config = config;
+    }
+
+    /// Where new particles are emitted from, in world space
+    pub fn set_position(&mut self, position: Vector2<f32>) {
+        self.position = position;
+    }
+
+    pub fn get_position(&self) -> Vector2<f32> {
+        self.position
+    }
+
+    /// Stops or resumes continuous emission. Particles already alive keep moving either way.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn get_particle_count(&self) -> usize {
+        self.particles.len()
+    }
+
+    /// Emits a number of particles at once, e.g. for an explosion. Still capped by max_particles.
+    pub fn burst(&mut self, count: usize) {
+        let count = count.min(self.config.max_particles.saturating_sub(self.particles.len()));
+        let mut rng = rand::rng();
+        for _ in 0..count {
+            let particle = self.spawn_particle(&mut rng);
+            self.particles.push(particle);
+        }
+    }
+
+    /// Removes every live particle
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.emission_accumulator = 0.0;
+    }
+
+    /// Emits, ages and moves the particles, then uploads the new batch to the emitter's object in the list
+    pub fn update(&mut self, delta_time: f32, graphics_list: &MasterGraphicsList) {
+        self.particles.retain_mut(|particle| {
+            particle.age += delta_time;
+            particle.age < particle.lifetime
+        });
+
+        let gravity = Vector2::new(self.config.gravity[0], self.config.gravity[1]);
+        for particle in self.particles.iter_mut() {
+            particle.velocity += gravity * delta_time;
+            particle.position += particle.velocity * delta_time;
+        }
+
+        if self.enabled && self.config.emission_rate > 0.0 {
+            self.emission_accumulator += self.config.emission_rate * delta_time;
+            let due = self.emission_accumulator as usize;
+            self.emission_accumulator -= due as f32;
+            self.burst(due);
+        }
+
+        self.upload(graphics_list);
+    }
+
+    fn spawn_particle(&self, rng: &mut impl Rng) -> Particle {
+        let angle = self.config.direction + rng.random_range(-1.0..=1.0) * self.config.spread;
+        let speed = Self::random_between(rng, self.config.speed_min, self.config.speed_max);
+
+        Particle {
+            position: self.position,
+            velocity: Vector2::new(angle.cos(), angle.sin()) * speed,
+            age: 0.0,
+            lifetime: Self::random_between(rng, self.config.lifetime_min, self.config.lifetime_max).max(f32::EPSILON),
+        }
+    }
+
+    fn random_between(rng: &mut impl Rng, min: f32, max: f32) -> f32 {
+        if max > min {
+            rng.random_range(min..=max)
+        } else {
+            min
+        }
+    }
+
+    fn upload(&self, graphics_list: &MasterGraphicsList) {
+        let Some(object) = graphics_list.get_object(&self.name) else {
+            return;
+        };
+
+        let mut vertices = Vec::with_capacity(self.particles.len() * 12);
+        let mut texture_coords = Vec::with_capacity(self.particles.len() * 12);
+        let mut colors = Vec::with_capacity(self.particles.len() * 24);
+
+        for particle in &self.particles {
+            let t = particle.age / particle.lifetime;
+            let half_size = (self.config.start_size + (self.config.end_size - self.config.start_size) * t) / 2.0;
+            let color: [f32; 4] = std::array::from_fn(|channel| {
+                self.config.start_color[channel] + (self.config.end_color[channel] - self.config.start_color[channel]) * t
+            });
+
+            let (left, right) = (particle.position.x - half_size, particle.position.x + half_size);
+            let (bottom, top) = (particle.position.y - half_size, particle.position.y + half_size);
+            vertices.extend_from_slice(&[left, top, left, bottom, right, bottom, left, top, right, bottom, right, top]);
+            texture_coords.extend_from_slice(&[0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 1.0, 1.0]);
+            for _ in 0..6 {
+                colors.extend_from_slice(&color);
+            }
+        }
+
+        object.write().unwrap().set_colored_geometry(vertices, texture_coords, colors);
+    }
+}
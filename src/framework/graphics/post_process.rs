@@ -0,0 +1,257 @@
+use std::ffi::CString;
+
+use gl::types::{GLenum, GLint, GLuint};
+
+use crate::framework::error::{EngineError, EngineResult};
+
+use super::{compile::create_shader_program, gl_check, internal_object::{vao::VAO, vbo::VBO}};
+
+// Shared by every effect: a fullscreen quad passing its texture coordinates on as TexCoord
+const POST_VERTEX_SHADER: &str = r#"
+#version 330 core
+layout(location = 0) in vec2 position;
+layout(location = 1) in vec2 texCoord;
+
+out vec2 TexCoord;
+
+void main() {
+    gl_Position = vec4(position, 0.0, 1.0);
+    TexCoord = texCoord;
+}
+"#;
+
+/// An offscreen framebuffer with a color texture the next pass can sample, plus a depth buffer for the scene pass
+struct RenderTarget {
+    framebuffer: GLuint,
+    color_texture: GLuint,
+    depth_renderbuffer: GLuint,
+    width: i32,
+    height: i32,
+}
+
+impl RenderTarget {
+    fn new(width: i32, height: i32, srgb: bool) -> EngineResult<Self> {
+        let mut target = RenderTarget { framebuffer: 0, color_texture: 0, depth_renderbuffer: 0, width, height };
+
+        unsafe {
+            gl::GenFramebuffers(1, &mut target.framebuffer);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, target.framebuffer);
+
+            gl::GenTextures(1, &mut target.color_texture);
+            gl::BindTexture(gl::TEXTURE_2D, target.color_texture);
+            let internal_format = if srgb { gl::SRGB8_ALPHA8 } else { gl::RGBA8 }; // sRGB keeps dark tones precise between passes
+            gl::TexImage2D(gl::TEXTURE_2D, 0, internal_format as GLint, width, height, 0, gl::RGBA, gl::UNSIGNED_BYTE, std::ptr::null());
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as GLint);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+            gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_2D, target.color_texture, 0);
+
+            gl::GenRenderbuffers(1, &mut target.depth_renderbuffer);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, target.depth_renderbuffer);
+            gl::RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH_COMPONENT24, width, height);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+            gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, gl::RENDERBUFFER, target.depth_renderbuffer);
+
+            let status: GLenum = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            if status != gl::FRAMEBUFFER_COMPLETE {
+                gl_check::check_gl_errors("framebuffer creation");
+                // Drop cleans up whatever was created
+                return Err(EngineError::Framebuffer(format!("{}x{} target is incomplete (status {:#x})", width, height, status)));
+            }
+        }
+
+        Ok(target)
+    }
+}
+
+impl Drop for RenderTarget {
+    fn drop(&mut self) {
+        if !gl_check::is_context_alive() {
+            return; // The context and everything in it is already gone
+        }
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.framebuffer);
+            gl::DeleteTextures(1, &self.color_texture);
+            gl::DeleteRenderbuffers(1, &self.depth_renderbuffer);
+        }
+    }
+}
+
+struct FullscreenQuad {
+    vao: VAO,
+    _position_vbo: VBO, // Kept alive for the VAO
+    _tex_vbo: VBO,
+}
+
+/// Renders the scene into an offscreen target, then runs it through a chain of fragment shaders,
+/// ping-ponging between two targets and writing the last pass to the screen.
+///
+/// Effect shaders receive `in vec2 TexCoord`, the previous pass as `uniform sampler2D screenTexture`,
+/// the target size in pixels as `uniform vec2 resolution` and seconds since the first post-processed frame as `uniform float time`.
+pub(crate) struct PostProcessor {
+    effects: Vec<(String, GLuint)>, // Name and shader program, applied in order
+    targets: Vec<RenderTarget>, // Up to two, created on first use and recreated when the viewport size changes
+    quad: Option<FullscreenQuad>,
+    time: f32,
+    srgb: bool, // Targets store sRGB-encoded colors, matching an sRGB default framebuffer
+}
+
+impl PostProcessor {
+    /// Doesn't touch GL, so headless controllers can own one
+    pub(crate) fn new() -> Self {
+        PostProcessor {
+            effects: Vec::new(),
+            targets: Vec::new(),
+            quad: None,
+            time: 0.0,
+            srgb: false,
+        }
+    }
+
+    pub(crate) fn set_srgb(&mut self, srgb: bool) {
+        if self.srgb != srgb {
+            self.srgb = srgb;
+            self.targets.clear(); // Recreated in the new format on the next frame
+        }
+    }
+
+    pub(crate) fn is_active(&self) -> bool {
+        !self.effects.is_empty()
+    }
+
+    /// Compiles the effect and appends it to the chain. An existing effect with the same name is replaced in place.
+    pub(crate) fn add_effect(&mut self, name: &str, fragment_shader_src: &str) -> EngineResult<()> {
+        let program = create_shader_program(POST_VERTEX_SHADER, fragment_shader_src)?;
+        match self.effects.iter_mut().find(|(effect_name, _)| effect_name == name) {
+            Some((_, existing)) => {
+                unsafe { gl::DeleteProgram(*existing); }
+                *existing = program;
+            }
+            None => self.effects.push((name.to_owned(), program)),
+        }
+        Ok(())
+    }
+
+    pub(crate) fn remove_effect(&mut self, name: &str) -> bool {
+        let Some(index) = self.effects.iter().position(|(effect_name, _)| effect_name == name) else {
+            return false;
+        };
+        let (_, program) = self.effects.remove(index);
+        unsafe { gl::DeleteProgram(program); }
+        if self.effects.is_empty() {
+            self.targets.clear(); // Free the offscreen memory until an effect is added again
+        }
+        true
+    }
+
+    pub(crate) fn get_effect_names(&self) -> Vec<String> {
+        self.effects.iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    /// Redirects drawing into the first offscreen target, sized to the viewport.
+    /// On failure the caller should draw straight to the screen instead.
+    pub(crate) fn begin(&mut self, width: i32, height: i32) -> EngineResult<()> {
+        let target_count = if self.effects.len() > 1 { 2 } else { 1 };
+        let resized = self.targets.first().is_some_and(|target| target.width != width || target.height != height);
+        if resized || self.targets.len() != target_count {
+            self.targets.clear();
+            for _ in 0..target_count {
+                self.targets.push(RenderTarget::new(width.max(1), height.max(1), self.srgb)?);
+            }
+        }
+        if self.quad.is_none() {
+            self.quad = Some(Self::create_quad());
+        }
+
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.targets[0].framebuffer);
+            gl::Viewport(0, 0, width, height);
+        }
+        Ok(())
+    }
+
+    /// Runs every effect, the last one drawing into the given viewport of the default framebuffer
+    pub(crate) fn finish(&mut self, viewport: (i32, i32, i32, i32), delta_time: f32) {
+        self.time += delta_time;
+        let Some(quad) = &self.quad else {
+            return;
+        };
+
+        unsafe {
+            gl::Disable(gl::DEPTH_TEST);
+            gl::Disable(gl::BLEND); // Each pass replaces its target, the scene's alpha was already blended
+            gl::ActiveTexture(gl::TEXTURE0);
+        }
+        quad.vao.bind();
+
+        for (index, (name, program)) in self.effects.iter().enumerate() {
+            let source = &self.targets[index % self.targets.len()];
+            let last = index + 1 == self.effects.len();
+
+            unsafe {
+                if last {
+                    gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+                    gl::Viewport(viewport.0, viewport.1, viewport.2, viewport.3);
+                } else {
+                    let destination = &self.targets[(index + 1) % self.targets.len()];
+                    gl::BindFramebuffer(gl::FRAMEBUFFER, destination.framebuffer);
+                    gl::Viewport(0, 0, destination.width, destination.height);
+                }
+
+                gl::UseProgram(*program);
+                gl::BindTexture(gl::TEXTURE_2D, source.color_texture);
+                Self::set_uniforms(*program, name, source.width as f32, source.height as f32, self.time);
+                gl::DrawArrays(gl::TRIANGLE_FAN, 0, 4);
+            }
+        }
+
+        VAO::unbind();
+        unsafe {
+            gl::UseProgram(0);
+            gl::Enable(gl::DEPTH_TEST);
+            gl::Enable(gl::BLEND);
+        }
+        gl_check::check_gl_errors("post-processing");
+    }
+
+    unsafe fn set_uniforms(program: GLuint, name: &str, width: f32, height: f32, time: f32) {
+        let screen_texture_location = gl::GetUniformLocation(program, CString::new("screenTexture").unwrap().as_ptr());
+        if screen_texture_location == -1 {
+            log::trace!("Uniform 'screenTexture' not found in post effect '{}'", name);
+        } else {
+            gl::Uniform1i(screen_texture_location, 0);
+        }
+
+        // Optional, plenty of effects don't need them
+        let resolution_location = gl::GetUniformLocation(program, CString::new("resolution").unwrap().as_ptr());
+        if resolution_location != -1 {
+            gl::Uniform2f(resolution_location, width, height);
+        }
+        let time_location = gl::GetUniformLocation(program, CString::new("time").unwrap().as_ptr());
+        if time_location != -1 {
+            gl::Uniform1f(time_location, time);
+        }
+    }
+
+    fn create_quad() -> FullscreenQuad {
+        let position_vbo = VBO::new(&[1.0, 1.0, 1.0, -1.0, -1.0, -1.0, -1.0, 1.0]);
+        let tex_vbo = VBO::new(&[1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0]);
+        let mut vao = VAO::new();
+        vao.setup_vertex_attributes(vec![(position_vbo.id(), 2, 0), (tex_vbo.id(), 2, 1)], None);
+        FullscreenQuad { vao, _position_vbo: position_vbo, _tex_vbo: tex_vbo }
+    }
+}
+
+impl Drop for PostProcessor {
+    fn drop(&mut self) {
+        if !gl_check::is_context_alive() {
+            return; // The context and everything in it is already gone
+        }
+        for (_, program) in &self.effects {
+            unsafe { gl::DeleteProgram(*program); }
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+use std::fs;
+use std::sync::RwLock;
+use gl::types::GLuint;
+
+use crate::framework::error::{EngineError, EngineResult};
+
+use super::compile::create_shader_program;
+
+// Built-in shader for untextured objects, coloring each vertex from attribute location 2
+const COLOR_VERTEX_SHADER: &str = r#"
+#version 330 core
+layout(location = 0) in vec2 position;
+layout(location = 2) in vec4 color;
+
+uniform mat4 projection;
+uniform mat4 model;
+
+out vec4 VertexColor;
+
+void main() {
+    gl_Position = projection * model * vec4(position, 0.0, 1.0);
+    VertexColor = color;
+}
+"#;
+
+const COLOR_FRAGMENT_SHADER: &str = r#"
+#version 330 core
+in vec4 VertexColor;
+out vec4 FragColor;
+
+void main() {
+    FragColor = VertexColor;
+}
+"#;
+
+/// Caches compiled shader programs so objects using the same GLSL share one program instead of each compiling their own.
+/// Programs are found either by a name given when loading, or by the sources themselves.
+pub struct ShaderManager {
+    programs: RwLock<HashMap<String, GLuint>>, // Name -> program
+    programs_by_source: RwLock<HashMap<(String, String), GLuint>>, // (Vertex source, fragment source) -> program
+}
+
+impl ShaderManager {
+    /// Name the built-in vertex color shader is stored under
+    pub const COLOR_SHADER: &'static str = "rusted_open_color";
+
+    pub fn new() -> Self {
+        ShaderManager {
+            programs: RwLock::new(HashMap::new()),
+            programs_by_source: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the program for these sources, compiling them only the first time they are seen
+    pub fn get_or_compile(&self, vertex_shader_src: &str, fragment_shader_src: &str) -> EngineResult<GLuint> {
+        let key = (vertex_shader_src.to_owned(), fragment_shader_src.to_owned());
+        if let Some(&program) = self.programs_by_source.read().unwrap().get(&key) {
+            return Ok(program);
+        }
+
+        let mut programs_by_source = self.programs_by_source.write().unwrap();
+        // Another thread may have compiled it while we waited for the lock
+        if let Some(&program) = programs_by_source.get(&key) {
+            return Ok(program);
+        }
+        let program = create_shader_program(vertex_shader_src, fragment_shader_src)?;
+        programs_by_source.insert(key, program);
+        Ok(program)
+    }
+
+    /// Compiles (or reuses) a program and stores it under name. A name that is already loaded keeps its existing program.
+    pub fn load_shader(&self, name: &str, vertex_shader_src: &str, fragment_shader_src: &str) -> EngineResult<GLuint> {
+        if let Some(program) = self.get_shader_program(name) {
+            return Ok(program);
+        }
+
+        let program = self.get_or_compile(vertex_shader_src, fragment_shader_src)?;
+        self.programs.write().unwrap().insert(name.to_string(), program);
+        Ok(program)
+    }
+
+    /// Reads both stages from disk and loads them under name
+    pub fn load_shader_from_files(&self, name: &str, vertex_path: &str, fragment_path: &str) -> EngineResult<GLuint> {
+        if let Some(program) = self.get_shader_program(name) {
+            return Ok(program);
+        }
+
+        let vertex_shader_src = fs::read_to_string(vertex_path).map_err(|e| EngineError::io(vertex_path, e))?;
+        let fragment_shader_src = fs::read_to_string(fragment_path).map_err(|e| EngineError::io(fragment_path, e))?;
+        self.load_shader(name, &vertex_shader_src, &fragment_shader_src)
+    }
+
+    /// The built-in shader for untextured objects, compiled on first use. It draws the object's vertex colors
+    /// (see Generic2DGraphicsObject::set_vertex_colors) and needs no texture or uniforms of its own.
+    pub fn get_color_shader(&self) -> EngineResult<GLuint> {
+        self.load_shader(Self::COLOR_SHADER, COLOR_VERTEX_SHADER, COLOR_FRAGMENT_SHADER)
+    }
+
+    pub fn has_shader(&self, name: &str) -> bool {
+        self.programs.read().unwrap().contains_key(name)
+    }
+
+    pub fn get_shader_program(&self, name: &str) -> Option<GLuint> {
+        self.programs.read().unwrap().get(name).copied()
+    }
+
+    /// Number of distinct compiled programs
+    pub fn get_program_count(&self) -> usize {
+        self.programs_by_source.read().unwrap().len()
+    }
+}
+
+impl Default for ShaderManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
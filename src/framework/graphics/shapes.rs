@@ -0,0 +1,52 @@
+use nalgebra::Vector3;
+
+use crate::framework::error::EngineResult;
+
+use super::{internal_object::graphics_object::Generic2DGraphicsObject, shader_manager::ShaderManager};
+
+// Untextured quads drawn with the ShaderManager's built-in color shader, for prototypes and simple UI
+// that shouldn't need a placeholder texture for every rectangle. Recolor them later with set_vertex_colors.
+
+/// A width x height rectangle in one color, centered on position
+pub fn solid_quad(name: &str, width: f32, height: f32, color: [f32; 4], position: Vector3<f32>, shader_manager: &ShaderManager) -> EngineResult<Generic2DGraphicsObject> {
+    gradient_quad(name, width, height, [color; 4], position, shader_manager)
+}
+
+/// A rectangle blending between its corner colors, given as top left, top right, bottom right, bottom left
+pub fn gradient_quad(name: &str, width: f32, height: f32, corner_colors: [[f32; 4]; 4], position: Vector3<f32>, shader_manager: &ShaderManager) -> EngineResult<Generic2DGraphicsObject> {
+    let shader_program = shader_manager.get_color_shader()?;
+    let half_width = width / 2.0;
+    let half_height = height / 2.0;
+    let vertex_data = vec![
+        -half_width, half_height,
+        half_width, half_height,
+        half_width, -half_height,
+        -half_width, -half_height,
+    ];
+    let texture_coords = vec![0.0, 1.0, 1.0, 1.0, 1.0, 0.0, 0.0, 0.0]; // Unused by the color shader, but keeps custom shaders working
+
+    let mut object = Generic2DGraphicsObject::new(
+        name.to_owned(),
+        vertex_data,
+        texture_coords,
+        shader_program,
+        position,
+        0.0,
+        1.0,
+        None,
+        None,
+        None,
+    );
+    object.set_vertex_colors(corner_colors.concat());
+    Ok(object)
+}
+
+/// Top-to-bottom gradient, e.g. a sky
+pub fn vertical_gradient_quad(name: &str, width: f32, height: f32, top: [f32; 4], bottom: [f32; 4], position: Vector3<f32>, shader_manager: &ShaderManager) -> EngineResult<Generic2DGraphicsObject> {
+    gradient_quad(name, width, height, [top, top, bottom, bottom], position, shader_manager)
+}
+
+/// Left-to-right gradient, e.g. a health bar fill
+pub fn horizontal_gradient_quad(name: &str, width: f32, height: f32, left: [f32; 4], right: [f32; 4], position: Vector3<f32>, shader_manager: &ShaderManager) -> EngineResult<Generic2DGraphicsObject> {
+    gradient_quad(name, width, height, [left, right, right, left], position, shader_manager)
+}
@@ -0,0 +1,214 @@
+use std::{collections::HashMap, fs, sync::{Arc, RwLock}};
+
+use gl::types::GLuint;
+use nalgebra::Vector3;
+use serde::Deserialize;
+
+use crate::framework::error::{EngineError, EngineResult};
+
+use super::{internal_object::graphics_object::Generic2DGraphicsObject, util::master_graphics_list::MasterGraphicsList};
+
+/// Where one character sits in the font atlas, in pixels from the atlas' top-left corner
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub struct Glyph {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    #[serde(default)]
+    pub x_offset: f32, // Shift from the pen position to the glyph's left edge
+    #[serde(default)]
+    pub y_offset: f32, // Shift from the line's top to the glyph's top edge
+    pub x_advance: f32, // How far the pen moves after this glyph
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct FontMetrics {
+    atlas_width: f32,
+    atlas_height: f32,
+    line_height: f32,
+    glyphs: HashMap<String, Glyph>, // Single-character keys, JSON objects can't have char keys
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// A bitmap font: an atlas texture plus glyph metrics loaded from JSON of the form
+/// `{ "atlas_width": 256, "atlas_height": 256, "line_height": 18, "glyphs": { "A": { "x": 0, "y": 0, "width": 10, "height": 16, "x_advance": 11 } } }`.
+pub struct BitmapFont {
+    texture_id: GLuint,
+    atlas_width: f32,
+    atlas_height: f32,
+    line_height: f32,
+    glyphs: HashMap<char, Glyph>,
+}
+
+impl BitmapFont {
+    /// Loads glyph metrics for an atlas texture that has already been loaded, e.g. through the TextureManager
+    pub fn load(metrics_path: &str, texture_id: GLuint) -> EngineResult<Self> {
+        let contents = fs::read_to_string(metrics_path).map_err(|e| EngineError::io(metrics_path, e))?;
+        let metrics: FontMetrics = serde_json::from_str(&contents).map_err(|e| EngineError::parse(metrics_path, e))?;
+
+        let glyphs = metrics.glyphs
+            .into_iter()
+            .filter_map(|(key, glyph)| {
+                let mut chars = key.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(character), None) => Some((character, glyph)),
+                    _ => {
+                        log::warn!("Skipping glyph '{}' in {}, keys must be a single character", key, metrics_path);
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        Ok(BitmapFont {
+            texture_id,
+            atlas_width: metrics.atlas_width,
+            atlas_height: metrics.atlas_height,
+            line_height: metrics.line_height,
+            glyphs,
+        })
+    }
+
+    pub fn get_texture_id(&self) -> GLuint {
+        self.texture_id
+    }
+
+    pub fn get_line_height(&self) -> f32 {
+        self.line_height
+    }
+
+    /// Size of the text block in atlas pixels, as (width of the widest line, total line height)
+    pub fn measure(&self, text: &str) -> (f32, f32) {
+        let width = text.lines().map(|line| self.line_width(line)).fold(0.0, f32::max);
+        let height = text.lines().count().max(1) as f32 * self.line_height;
+        (width, height)
+    }
+
+    fn line_width(&self, line: &str) -> f32 {
+        line.chars().filter_map(|character| self.glyphs.get(&character)).map(|glyph| glyph.x_advance).sum()
+    }
+
+    /// Builds two triangles per glyph, for drawing with gl::TRIANGLES. pixel_scale converts atlas pixels to world units.
+    /// The text's top-left (or top-center/top-right, per align) is at the origin; characters missing from the font are skipped.
+    pub fn build_mesh(&self, text: &str, pixel_scale: f32, align: TextAlign) -> (Vec<f32>, Vec<f32>) {
+        let mut vertices = Vec::new();
+        let mut texture_coords = Vec::new();
+
+        for (line_index, line) in text.lines().enumerate() {
+            let line_width = self.line_width(line);
+            let mut pen_x = match align {
+                TextAlign::Left => 0.0,
+                TextAlign::Center => -line_width / 2.0,
+                TextAlign::Right => -line_width,
+            };
+            let line_top = -(line_index as f32) * self.line_height;
+
+            for character in line.chars() {
+                let Some(glyph) = self.glyphs.get(&character) else {
+                    continue;
+                };
+
+                let left = (pen_x + glyph.x_offset) * pixel_scale;
+                let right = left + glyph.width * pixel_scale;
+                let top = (line_top - glyph.y_offset) * pixel_scale;
+                let bottom = top - glyph.height * pixel_scale;
+
+                // Atlas metrics count down from the top, texture coordinates count up from the bottom
+                let u_left = glyph.x / self.atlas_width;
+                let u_right = (glyph.x + glyph.width) / self.atlas_width;
+                let v_top = 1.0 - glyph.y / self.atlas_height;
+                let v_bottom = 1.0 - (glyph.y + glyph.height) / self.atlas_height;
+
+                vertices.extend_from_slice(&[left, top, left, bottom, right, bottom, left, top, right, bottom, right, top]);
+                texture_coords.extend_from_slice(&[u_left, v_top, u_left, v_bottom, u_right, v_bottom, u_left, v_top, u_right, v_bottom, u_right, v_top]);
+
+                pen_x += glyph.x_advance;
+            }
+        }
+
+        (vertices, texture_coords)
+    }
+}
+
+/// A piece of text drawn through the MasterGraphicsList as an ordinary graphics object, so it moves, rotates,
+/// layers and despawns like anything else. Keep the TextObject around to change the text later.
+pub struct TextObject {
+    name: String,
+    font: Arc<BitmapFont>,
+    text: String,
+    pixel_scale: f32,
+    align: TextAlign,
+}
+
+impl TextObject {
+    /// pixel_scale is the size of one atlas pixel in world units
+    pub fn new(name: &str, font: Arc<BitmapFont>, text: &str, pixel_scale: f32, align: TextAlign) -> Self {
+        TextObject {
+            name: name.to_owned(),
+            font,
+            text: text.to_owned(),
+            pixel_scale,
+            align,
+        }
+    }
+
+    /// Creates the graphics object and adds it to the list. The shader needs the usual position/texCoord attributes
+    /// and should sample the bound texture.
+    pub fn add_to(&mut self, graphics_list: &MasterGraphicsList, shader_program: GLuint, position: Vector3<f32>) -> EngineResult<String> {
+        let (vertices, texture_coords) = self.font.build_mesh(&self.text, self.pixel_scale, self.align);
+        let mut object = Generic2DGraphicsObject::new(
+            self.name.clone(),
+            vertices,
+            texture_coords,
+            shader_program,
+            position,
+            0.0,
+            1.0,
+            Some(self.font.get_texture_id()),
+            None,
+            None,
+        );
+        object.set_draw_mode(gl::TRIANGLES);
+
+        // The list may rename the object to avoid a collision
+        self.name = graphics_list.add_object(Arc::new(RwLock::new(object)))?;
+        Ok(self.name.clone())
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_text(&self) -> &str {
+        &self.text
+    }
+
+    /// Changes the text and rebuilds the object's mesh in the list
+    pub fn set_text(&mut self, text: &str, graphics_list: &MasterGraphicsList) {
+        if self.text == text {
+            return;
+        }
+        self.text = text.to_owned();
+        self.rebuild(graphics_list);
+    }
+
+    pub fn set_align(&mut self, align: TextAlign, graphics_list: &MasterGraphicsList) {
+        self.align = align;
+        self.rebuild(graphics_list);
+    }
+
+    fn rebuild(&self, graphics_list: &MasterGraphicsList) {
+        if let Some(object) = graphics_list.get_object(&self.name) {
+            let (vertices, texture_coords) = self.font.build_mesh(&self.text, self.pixel_scale, self.align);
+            object.write().unwrap().set_geometry(vertices, texture_coords);
+        }
+    }
+}
@@ -1,21 +1,87 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::sync::RwLock;
-use gl::types::{GLint, GLsizei, GLuint};
+use gl::types::{GLenum, GLint, GLsizei, GLuint};
 use image::{self, GenericImageView}; // Ensure you have this crate in your Cargo.toml
 
+use crate::framework::error::{EngineError, EngineResult};
+
+use super::gl_check;
+
+/// How texels are sampled when a texture is drawn larger or smaller than its pixel size
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextureFilter {
+    /// Hard pixel edges, for pixel art
+    #[default]
+    Nearest,
+    /// Smooth blending between neighbouring texels
+    Linear,
+}
+
+/// Sampling settings applied to a texture when it is uploaded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TextureSettings {
+    pub filter: TextureFilter,
+    pub mipmaps: bool, // Generate mipmaps and sample them when the texture is drawn smaller than its size
+    pub srgb: bool, // Store as SRGB8_ALPHA8 so shaders sample linear colors. Leave off for data such as normal maps.
+}
+
+impl TextureSettings {
+    pub fn new(filter: TextureFilter, mipmaps: bool) -> Self {
+        TextureSettings { filter, mipmaps, srgb: false }
+    }
+
+    fn internal_format(&self) -> GLenum {
+        if self.srgb { gl::SRGB8_ALPHA8 } else { gl::RGBA8 }
+    }
+
+    fn min_filter(&self) -> GLenum {
+        match (self.filter, self.mipmaps) {
+            (TextureFilter::Nearest, false) => gl::NEAREST,
+            (TextureFilter::Linear, false) => gl::LINEAR,
+            (TextureFilter::Nearest, true) => gl::NEAREST_MIPMAP_NEAREST,
+            (TextureFilter::Linear, true) => gl::LINEAR_MIPMAP_LINEAR,
+        }
+    }
+
+    fn mag_filter(&self) -> GLenum {
+        match self.filter {
+            TextureFilter::Nearest => gl::NEAREST,
+            TextureFilter::Linear => gl::LINEAR,
+        }
+    }
+}
+
 pub struct TextureManager {
     textures: RwLock<HashMap<String, GLuint>>,
+    default_settings: RwLock<TextureSettings>, // Used by every load that doesn't pass its own settings
+    srgb_textures: RwLock<HashSet<GLuint>>, // Stored as SRGB8_ALPHA8, so reloads keep the format
 }
 
 impl TextureManager {
     pub fn new() -> Self {
         TextureManager {
             textures: RwLock::new(HashMap::new()),
+            default_settings: RwLock::new(TextureSettings::default()),
+            srgb_textures: RwLock::new(HashSet::new()),
         }
     }
 
-    pub fn load_texture(&self, name: &str, path: &str) -> Result<GLuint, String> {
+    /// Settings for textures loaded from now on, already loaded textures keep theirs
+    pub fn set_default_settings(&self, settings: TextureSettings) {
+        *self.default_settings.write().unwrap() = settings;
+    }
+
+    pub fn get_default_settings(&self) -> TextureSettings {
+        *self.default_settings.read().unwrap()
+    }
+
+    pub fn load_texture(&self, name: &str, path: &str) -> EngineResult<GLuint> {
+        self.load_texture_with_settings(name, path, self.get_default_settings())
+    }
+
+    /// Like load_texture, but with its own filtering instead of the defaults, e.g. a smooth background in a pixel-art game
+    pub fn load_texture_with_settings(&self, name: &str, path: &str, settings: TextureSettings) -> EngineResult<GLuint> {
         let mut textures = self.textures.write().unwrap();
         
         // Check if texture is already loaded
@@ -24,30 +90,96 @@ impl TextureManager {
         }
 
         // Load the texture and store it
-        match Self::load_texture_from_file(path) {
+        match Self::load_texture_from_file(path, settings) {
             Ok(texture_id) => {
                 textures.insert(name.to_string(), texture_id);
+                self.track_format(texture_id, settings);
                 Ok(texture_id) // Return the newly loaded texture ID
             },
             Err(e) => Err(e), // Pass the error up
         }
     }
 
-    fn load_texture_from_file(path: &str) -> Result<GLuint, String> {
-        let img = image::open(path).map_err(|_| "Failed to load texture".to_string())?;
-        let data = img.to_rgba8();
-        let (width, height) = img.dimensions();
-    
+    /// Decodes an encoded image (png, jpg, ...) held in memory, e.g. read from a pak archive, and stores it under name
+    pub fn load_texture_from_memory(&self, name: &str, bytes: &[u8]) -> EngineResult<GLuint> {
+        let mut textures = self.textures.write().unwrap();
+
+        if let Some(&texture_id) = textures.get(name) {
+            return Ok(texture_id);
+        }
+
+        let img = image::load_from_memory(bytes).map_err(|source| EngineError::Image { path: name.to_string(), source })?;
+        let settings = self.get_default_settings();
+        let texture_id = Self::upload_texture(&img, settings);
+        textures.insert(name.to_string(), texture_id);
+        self.track_format(texture_id, settings);
+        Ok(texture_id)
+    }
+
+    /// Replaces the pixels of an already loaded texture in place, keeping its ID so objects using it pick up the change.
+    /// Loads it fresh if nothing is stored under name yet.
+    pub fn reload_texture_from_memory(&self, name: &str, bytes: &[u8]) -> EngineResult<GLuint> {
+        let existing = self.textures.read().unwrap().get(name).copied();
+        match existing {
+            Some(texture_id) => {
+                let img = image::load_from_memory(bytes).map_err(|source| EngineError::Image { path: name.to_string(), source })?;
+                // Keep whatever format and filtering the texture has, but its mipmaps are stale after this
+                let srgb = self.srgb_textures.read().unwrap().contains(&texture_id);
+                Self::write_pixels(texture_id, &img, TextureSettings { srgb, ..TextureSettings::default() }.internal_format());
+                unsafe {
+                    gl::BindTexture(gl::TEXTURE_2D, texture_id);
+                    let mut min_filter: GLint = 0;
+                    gl::GetTexParameteriv(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, &mut min_filter);
+                    if min_filter as GLenum != gl::NEAREST && min_filter as GLenum != gl::LINEAR {
+                        gl::GenerateMipmap(gl::TEXTURE_2D);
+                    }
+                    gl::BindTexture(gl::TEXTURE_2D, 0);
+                }
+                Ok(texture_id)
+            }
+            None => self.load_texture_from_memory(name, bytes),
+        }
+    }
+
+    /// Changes the filtering of an already loaded texture. Its color format stays as loaded, srgb is ignored here.
+    /// Returns whether a texture is stored under name.
+    pub fn set_texture_settings(&self, name: &str, settings: TextureSettings) -> bool {
+        match self.textures.read().unwrap().get(name) {
+            Some(&texture_id) => {
+                Self::apply_settings(texture_id, settings);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn load_texture_from_file(path: &str, settings: TextureSettings) -> EngineResult<GLuint> {
+        let img = image::open(path).map_err(|source| EngineError::Image { path: path.to_string(), source })?;
+        Ok(Self::upload_texture(&img, settings))
+    }
+
+    pub(crate) fn upload_texture(img: &image::DynamicImage, settings: TextureSettings) -> GLuint {
         let mut texture: GLuint = 0;
         unsafe {
             gl::GenTextures(1, &mut texture);  // Generate texture ID
+        }
+        Self::write_pixels(texture, img, settings.internal_format());
+        Self::apply_settings(texture, settings);
+        texture // Return the texture ID
+    }
+
+    fn write_pixels(texture: GLuint, img: &image::DynamicImage, internal_format: GLenum) {
+        let data = img.to_rgba8();
+        let (width, height) = img.dimensions();
+
+        unsafe {
             gl::BindTexture(gl::TEXTURE_2D, texture);  // Bind texture
 
             // Upload the texture data
             gl::TexImage2D(
                 gl::TEXTURE_2D,
                 0,
-                gl::RGBA as GLint,
+                internal_format as GLint,
                 width as GLsizei,
                 height as GLsizei,
                 0,
@@ -56,17 +188,56 @@ impl TextureManager {
                 data.as_ptr() as *const _,
             );
 
-            // Set texture parameters
+            gl::BindTexture(gl::TEXTURE_2D, 0);  // Unbind the texture
+        }
+        gl_check::check_gl_errors("texture upload");
+    }
+
+    fn apply_settings(texture: GLuint, settings: TextureSettings) {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, texture);
+
             gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as GLint);
             gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as GLint);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as GLint);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, settings.min_filter() as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, settings.mag_filter() as GLint);
 
-            gl::GenerateMipmap(gl::TEXTURE_2D);  // Generate mipmaps
-            gl::BindTexture(gl::TEXTURE_2D, 0);  // Unbind the texture
+            if settings.mipmaps {
+                gl::GenerateMipmap(gl::TEXTURE_2D);
+            }
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+        gl_check::check_gl_errors("texture settings");
+    }
+
+    /// Stores a texture created elsewhere, e.g. on the background loader's shared context.
+    /// A different texture already stored under name is deleted, the manager owns what it stores.
+    pub fn insert_texture(&self, name: &str, texture_id: GLuint) {
+        let replaced = self.textures.write().unwrap().insert(name.to_string(), texture_id);
+        if let Some(old_texture_id) = replaced.filter(|&old_texture_id| old_texture_id != texture_id) {
+            self.srgb_textures.write().unwrap().remove(&old_texture_id);
+            if gl_check::is_context_alive() {
+                unsafe {
+                    gl::DeleteTextures(1, &old_texture_id);
+                }
+            }
         }
+    }
 
-        Ok(texture) // Return the texture ID
+    /// insert_texture for a texture uploaded with upload_texture, remembering its format
+    pub(crate) fn insert_uploaded_texture(&self, name: &str, texture_id: GLuint, settings: TextureSettings) {
+        self.insert_texture(name, texture_id);
+        self.track_format(texture_id, settings);
+    }
+
+    fn track_format(&self, texture_id: GLuint, settings: TextureSettings) {
+        if settings.srgb {
+            self.srgb_textures.write().unwrap().insert(texture_id);
+        }
+    }
+
+    pub fn has_texture(&self, name: &str) -> bool {
+        self.textures.read().unwrap().contains_key(name)
     }
 
     pub fn get_texture_id(&self, name: &str) -> Option<GLuint> {
@@ -75,12 +246,12 @@ impl TextureManager {
     }
 
     // New method to load all textures from a specified directory
-    pub fn load_textures_from_directory(&self, dir_path: &str) -> Result<(), String> {
-        let paths = fs::read_dir(dir_path).map_err(|_| "Failed to read directory".to_string())?;
+    pub fn load_textures_from_directory(&self, dir_path: &str) -> EngineResult<()> {
+        let paths = fs::read_dir(dir_path).map_err(|e| EngineError::io(dir_path, e))?;
 
         for path in paths {
-            let entry = path.map_err(|_| "Failed to read directory entry".to_string())?;
-            let file_name = entry.file_name().into_string().map_err(|_| "Invalid file name".to_string())?;
+            let entry = path.map_err(|e| EngineError::io(dir_path, e))?;
+            let file_name = entry.file_name().into_string().map_err(|name| EngineError::InvalidFileName(name.to_string_lossy().into_owned()))?;
             let full_path = entry.path();
 
             // Only load image files (you may want to check for specific extensions)
@@ -89,7 +260,8 @@ impl TextureManager {
                     if extension == "png" || extension == "jpg" || extension == "jpeg" {
                         // Load the texture with the file name (without extension)
                         let name = file_name.trim_end_matches(".png").trim_end_matches(".jpg").trim_end_matches(".jpeg");
-                        self.load_texture(name, full_path.to_str().unwrap()).map_err(|e| format!("Error loading texture '{}': {}", name, e))?;
+                        let path_str = full_path.to_str().ok_or_else(|| EngineError::InvalidFileName(full_path.display().to_string()))?;
+                        self.load_texture(name, path_str)?;
                     }
                 }
             }
@@ -98,3 +270,17 @@ impl TextureManager {
         Ok(())
     }
 }
+
+impl Drop for TextureManager {
+    fn drop(&mut self) {
+        if !gl_check::is_context_alive() {
+            return; // The context and everything in it is already gone
+        }
+        let textures = self.textures.read().unwrap();
+        for texture_id in textures.values() {
+            unsafe {
+                gl::DeleteTextures(1, texture_id);
+            }
+        }
+    }
+}
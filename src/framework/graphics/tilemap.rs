@@ -0,0 +1,182 @@
+use std::sync::{Arc, RwLock};
+
+use gl::types::GLuint;
+use nalgebra::Vector3;
+
+use crate::framework::error::{EngineError, EngineResult};
+
+use super::{internal_object::graphics_object::Generic2DGraphicsObject, util::master_graphics_list::MasterGraphicsList};
+
+/// A grid of tiles drawn from one atlas texture. The map is split into square chunks and each chunk is a single
+/// graphics object holding every tile in it, so a large map costs one draw call per chunk instead of one per tile.
+///
+/// Tiles are indexed row by row from the top-left, both in the grid and in the atlas. Texture coordinates are baked
+/// into the mesh, so the chunks use an ordinary textured shader rather than the atlas uniforms.
+pub struct Tilemap {
+    name: String,
+    width: usize, // In tiles
+    height: usize,
+    tiles: Vec<Option<usize>>, // Atlas index per cell, row-major; None leaves the cell empty
+    tile_size: f32, // World units per tile edge
+    atlas_columns: usize,
+    atlas_rows: usize,
+    chunk_size: usize, // Tiles per chunk edge
+    chunk_names: Vec<String>, // Row-major by chunk, filled in by add_to
+}
+
+impl Tilemap {
+    /// Creates an empty map. A chunk_size around 16-32 keeps both the chunk count and the cost of editing one tile low.
+    pub fn new(name: &str, width: usize, height: usize, tile_size: f32, atlas_columns: usize, atlas_rows: usize, chunk_size: usize) -> Self {
+        Tilemap {
+            name: name.to_owned(),
+            width,
+            height,
+            tiles: vec![None; width * height],
+            tile_size,
+            atlas_columns: atlas_columns.max(1),
+            atlas_rows: atlas_rows.max(1),
+            chunk_size: chunk_size.max(1),
+            chunk_names: Vec::new(),
+        }
+    }
+
+    /// Replaces every tile at once, e.g. with a level loaded from JSON. tiles must hold width * height entries.
+    pub fn set_tiles(&mut self, tiles: Vec<Option<usize>>, graphics_list: &MasterGraphicsList) -> EngineResult<()> {
+        if tiles.len() != self.width * self.height {
+            return Err(EngineError::InvalidTilemap {
+                name: self.name.clone(),
+                reason: format!("map is {}x{} tiles but {} were given", self.width, self.height, tiles.len()),
+            });
+        }
+        self.tiles = tiles;
+        for chunk_index in 0..self.chunk_names.len() {
+            self.rebuild_chunk(chunk_index, graphics_list);
+        }
+        Ok(())
+    }
+
+    pub fn get_tile(&self, x: usize, y: usize) -> Option<usize> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.tiles[y * self.width + x]
+    }
+
+    /// Changes one tile and rebuilds only the chunk containing it. Out-of-range cells are ignored.
+    pub fn set_tile(&mut self, x: usize, y: usize, tile: Option<usize>, graphics_list: &MasterGraphicsList) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        self.tiles[y * self.width + x] = tile;
+
+        let chunk_index = (y / self.chunk_size) * self.chunks_wide() + x / self.chunk_size;
+        if chunk_index < self.chunk_names.len() {
+            self.rebuild_chunk(chunk_index, graphics_list);
+        }
+    }
+
+    pub fn get_size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    pub fn get_tile_size(&self) -> f32 {
+        self.tile_size
+    }
+
+    /// Names of the chunk objects in the list, e.g. for setting their layer
+    pub fn get_chunk_names(&self) -> &[String] {
+        &self.chunk_names
+    }
+
+    /// Builds every chunk and adds them to the list, with the map's top-left corner at origin
+    pub fn add_to(&mut self, graphics_list: &MasterGraphicsList, shader_program: GLuint, texture_id: GLuint, origin: Vector3<f32>) -> EngineResult<()> {
+        let chunk_world_size = self.chunk_size as f32 * self.tile_size;
+        let mut chunk_names = Vec::new();
+
+        for chunk_y in 0..self.chunks_tall() {
+            for chunk_x in 0..self.chunks_wide() {
+                let (vertices, texture_coords) = self.build_chunk_mesh(chunk_x, chunk_y);
+                let position = origin + Vector3::new(chunk_x as f32 * chunk_world_size, -(chunk_y as f32) * chunk_world_size, 0.0);
+                let mut object = Generic2DGraphicsObject::new(
+                    format!("{}_chunk_{}_{}", self.name, chunk_x, chunk_y),
+                    vertices,
+                    texture_coords,
+                    shader_program,
+                    position,
+                    0.0,
+                    1.0,
+                    Some(texture_id),
+                    None,
+                    None,
+                );
+                object.set_draw_mode(gl::TRIANGLES);
+
+                // The list may rename the object to avoid a collision
+                chunk_names.push(graphics_list.add_object(Arc::new(RwLock::new(object)))?);
+            }
+        }
+
+        self.chunk_names = chunk_names;
+        Ok(())
+    }
+
+    /// Removes every chunk object from the list
+    pub fn remove_from(&mut self, graphics_list: &MasterGraphicsList) {
+        graphics_list.remove_many(self.chunk_names.drain(..));
+    }
+
+    fn chunks_wide(&self) -> usize {
+        self.width.div_ceil(self.chunk_size)
+    }
+
+    fn chunks_tall(&self) -> usize {
+        self.height.div_ceil(self.chunk_size)
+    }
+
+    fn rebuild_chunk(&self, chunk_index: usize, graphics_list: &MasterGraphicsList) {
+        let Some(object) = graphics_list.get_object(&self.chunk_names[chunk_index]) else {
+            return;
+        };
+        let chunks_wide = self.chunks_wide();
+        let (vertices, texture_coords) = self.build_chunk_mesh(chunk_index % chunks_wide, chunk_index / chunks_wide);
+        object.write().unwrap().set_geometry(vertices, texture_coords);
+    }
+
+    /// Two triangles per non-empty tile, relative to the chunk's top-left corner
+    fn build_chunk_mesh(&self, chunk_x: usize, chunk_y: usize) -> (Vec<f32>, Vec<f32>) {
+        let mut vertices = Vec::new();
+        let mut texture_coords = Vec::new();
+
+        let first_x = chunk_x * self.chunk_size;
+        let first_y = chunk_y * self.chunk_size;
+        for y in first_y..(first_y + self.chunk_size).min(self.height) {
+            for x in first_x..(first_x + self.chunk_size).min(self.width) {
+                let Some(tile) = self.tiles[y * self.width + x] else {
+                    continue;
+                };
+                if tile >= self.atlas_columns * self.atlas_rows {
+                    log::warn!("Tile index {} at ({}, {}) in '{}' is outside the atlas", tile, x, y, self.name);
+                    continue;
+                }
+
+                let left = (x - first_x) as f32 * self.tile_size;
+                let right = left + self.tile_size;
+                let top = -((y - first_y) as f32) * self.tile_size;
+                let bottom = top - self.tile_size;
+
+                // Atlas rows count down from the top, texture coordinates count up from the bottom
+                let column = tile % self.atlas_columns;
+                let row = tile / self.atlas_columns;
+                let u_left = column as f32 / self.atlas_columns as f32;
+                let u_right = (column + 1) as f32 / self.atlas_columns as f32;
+                let v_top = 1.0 - row as f32 / self.atlas_rows as f32;
+                let v_bottom = 1.0 - (row + 1) as f32 / self.atlas_rows as f32;
+
+                vertices.extend_from_slice(&[left, top, left, bottom, right, bottom, left, top, right, bottom, right, top]);
+                texture_coords.extend_from_slice(&[u_left, v_top, u_left, v_bottom, u_right, v_bottom, u_left, v_top, u_right, v_bottom, u_right, v_top]);
+            }
+        }
+
+        (vertices, texture_coords)
+    }
+}
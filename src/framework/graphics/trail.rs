@@ -0,0 +1,217 @@
+use std::{collections::VecDeque, sync::{Arc, RwLock}};
+
+use gl::types::GLuint;
+use nalgebra::{Vector2, Vector3};
+use serde::{Deserialize, Serialize};
+
+use crate::framework::error::EngineResult;
+
+use super::{internal_object::graphics_object::Generic2DGraphicsObject, util::master_graphics_list::MasterGraphicsList};
+
+/// Shape and fade of a trail. Every field has a default, so config JSON only needs the ones it changes.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct TrailConfig {
+    pub lifetime: f32, // Seconds a recorded point stays, which sets how long the trail is while moving
+    pub min_distance: f32, // World units the head has to move before another point is recorded
+    pub max_points: usize, // Oldest points are dropped beyond this
+    pub start_width: f32, // World units at the head
+    pub end_width: f32, // ...and at the tail, linearly in between
+    pub start_color: [f32; 4], // RGBA at the head
+    pub end_color: [f32; 4], // RGBA at the tail, set alpha to 0 for a fade out
+}
+
+impl Default for TrailConfig {
+    fn default() -> Self {
+        TrailConfig {
+            lifetime: 0.5,
+            min_distance: 0.01,
+            max_points: 64,
+            start_width: 0.05,
+            end_width: 0.0,
+            start_color: [1.0, 1.0, 1.0, 1.0],
+            end_color: [1.0, 1.0, 1.0, 0.0],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TrailPoint {
+    position: Vector2<f32>,
+    age: f32,
+}
+
+/// Records where something has been and draws a ribbon through those points that narrows and fades towards the tail,
+/// e.g. behind projectiles or a dash. The whole ribbon is one triangle strip in one graphics object.
+///
+/// Like ParticleEmitter, the object's shader receives an RGBA color per vertex at attribute location 2, so
+/// ShaderManager::get_color_shader works as is. Texture coordinates run from u = 0 at the head to 1 at the tail,
+/// with v = 0 and 1 on the two edges.
+pub struct TrailRenderer {
+    name: String,
+    config: TrailConfig,
+    points: VecDeque<TrailPoint>, // Newest first
+    head: Vector2<f32>, // Where the trail starts this frame
+    target: Option<String>, // Object in the list the head follows
+    emitting: bool,
+}
+
+impl TrailRenderer {
+    pub fn new(name: &str, config: TrailConfig) -> Self {
+        TrailRenderer {
+            name: name.to_owned(),
+            config,
+            points: VecDeque::new(),
+            head: Vector2::zeros(),
+            target: None,
+            emitting: true,
+        }
+    }
+
+    /// Creates the ribbon's graphics object and adds it to the list. It sits at the origin, its vertices are world positions.
+    pub fn add_to(&mut self, graphics_list: &MasterGraphicsList, shader_program: GLuint, texture_id: Option<GLuint>) -> EngineResult<String> {
+        let mut object = Generic2DGraphicsObject::new(
+            self.name.clone(),
+            Vec::new(),
+            Vec::new(),
+            shader_program,
+            Vector3::zeros(),
+            0.0,
+            1.0,
+            texture_id,
+            None,
+            None,
+        );
+        object.set_draw_mode(gl::TRIANGLE_STRIP);
+
+        // The list may rename the object to avoid a collision
+        self.name = graphics_list.add_object(Arc::new(RwLock::new(object)))?;
+        Ok(self.name.clone())
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_config(&self) -> &TrailConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: TrailConfig) {
+        self.config = config;
+    }
+
+    /// Makes the head follow an object in the list every update, or stop following with None
+    pub fn follow(&mut self, target: Option<&str>) {
+        self.target = target.map(str::to_owned);
+    }
+
+    pub fn get_target(&self) -> Option<&str> {
+        self.target.as_deref()
+    }
+
+    /// Moves the head by hand, for trails that don't follow an object
+    pub fn set_position(&mut self, position: Vector2<f32>) {
+        self.head = position;
+    }
+
+    pub fn get_position(&self) -> Vector2<f32> {
+        self.head
+    }
+
+    /// Stops or resumes recording. A trail that stops emitting shrinks away as its points expire.
+    pub fn set_emitting(&mut self, emitting: bool) {
+        self.emitting = emitting;
+    }
+
+    pub fn is_emitting(&self) -> bool {
+        self.emitting
+    }
+
+    pub fn get_point_count(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Removes every recorded point, e.g. after teleporting so the trail doesn't stretch across the jump
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+
+    /// Ages the points, records the head's new position and uploads the ribbon to the trail's object in the list
+    pub fn update(&mut self, delta_time: f32, graphics_list: &MasterGraphicsList) {
+        if let Some(target) = &self.target {
+            if let Some(object) = graphics_list.get_object(target) {
+                let position = object.read().unwrap().get_position();
+                self.head = Vector2::new(position.x, position.y);
+            }
+        }
+
+        for point in self.points.iter_mut() {
+            point.age += delta_time;
+        }
+        while self.points.back().is_some_and(|point| point.age >= self.config.lifetime) {
+            self.points.pop_back();
+        }
+
+        if self.emitting {
+            let moved = self.points.front().is_none_or(|newest| (self.head - newest.position).norm() >= self.config.min_distance);
+            if moved {
+                self.points.push_front(TrailPoint { position: self.head, age: 0.0 });
+                self.points.truncate(self.config.max_points.max(2));
+            }
+        }
+
+        self.upload(graphics_list);
+    }
+
+    fn upload(&self, graphics_list: &MasterGraphicsList) {
+        let Some(object) = graphics_list.get_object(&self.name) else {
+            return;
+        };
+
+        // While emitting the ribbon always reaches the head, even before it has moved far enough for a new point
+        let mut path: Vec<TrailPoint> = Vec::with_capacity(self.points.len() + 1);
+        if self.emitting && self.points.front().is_some_and(|newest| newest.position != self.head) {
+            path.push(TrailPoint { position: self.head, age: 0.0 });
+        }
+        path.extend(self.points.iter().copied());
+
+        if path.len() < 2 {
+            object.write().unwrap().set_colored_geometry(Vec::new(), Vec::new(), Vec::new());
+            return;
+        }
+
+        let mut vertices = Vec::with_capacity(path.len() * 4);
+        let mut texture_coords = Vec::with_capacity(path.len() * 4);
+        let mut colors = Vec::with_capacity(path.len() * 8);
+        let lifetime = self.config.lifetime.max(f32::EPSILON);
+
+        for (index, point) in path.iter().enumerate() {
+            // Direction along the trail at this point, from its neighbours
+            let previous = path[index.saturating_sub(1)].position;
+            let next = path[(index + 1).min(path.len() - 1)].position;
+            let along = next - previous;
+            let normal = if along.norm() > f32::EPSILON {
+                Vector2::new(-along.y, along.x).normalize()
+            } else {
+                Vector2::zeros()
+            };
+
+            let t = (point.age / lifetime).min(1.0);
+            let half_width = (self.config.start_width + (self.config.end_width - self.config.start_width) * t) / 2.0;
+            let color: [f32; 4] = std::array::from_fn(|channel| {
+                self.config.start_color[channel] + (self.config.end_color[channel] - self.config.start_color[channel]) * t
+            });
+
+            let left = point.position + normal * half_width;
+            let right = point.position - normal * half_width;
+            let u = index as f32 / (path.len() - 1) as f32;
+            vertices.extend_from_slice(&[left.x, left.y, right.x, right.y]);
+            texture_coords.extend_from_slice(&[u, 1.0, u, 0.0]);
+            colors.extend_from_slice(&color);
+            colors.extend_from_slice(&color);
+        }
+
+        object.write().unwrap().set_colored_geometry(vertices, texture_coords, colors);
+    }
+}
@@ -1 +1,3 @@
-pub mod master_graphics_list;
\ No newline at end of file
+pub mod master_graphics_list;
+pub mod object_pool;
+pub mod spawner;
\ No newline at end of file
@@ -1,10 +1,31 @@
-use std::{collections::HashMap, sync::{Arc, RwLock}};
-use nalgebra::Matrix4;
+use std::{collections::HashMap, sync::{Arc, Mutex, RwLock}};
+use gl::types::GLuint;
+use nalgebra::{Matrix4, Vector3};
 
-use crate::framework::graphics::internal_object::graphics_object::Generic2DGraphicsObject;
+use crate::framework::{error::{EngineError, EngineResult}, graphics::internal_object::{clip_rect::ClipRect, graphics_object::Generic2DGraphicsObject}};
+
+/// What add_object does when an object with the same name is already in the list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameCollisionPolicy {
+    Error, // Refuse to add the new object
+    Overwrite, // Replace the existing object
+    AutoSuffix, // Rename the new object with a numeric suffix (name_1, name_2...)
+}
+
+/// A change to the list that is held back until flush_commands, so it can be requested mid-iteration
+pub enum GraphicsListCommand {
+    Add(Arc<RwLock<Generic2DGraphicsObject>>),
+    Remove(String),
+    Modify(String, Box<dyn FnOnce(&mut Generic2DGraphicsObject) + Send>),
+}
 
 pub struct MasterGraphicsList {
     objects: Arc<RwLock<HashMap<String, Arc<RwLock<Generic2DGraphicsObject>>>>>, // Change key type to String
+    name_collision_policy: NameCollisionPolicy,
+    pending_commands: Mutex<Vec<GraphicsListCommand>>,
+    culling_enabled: bool, // Skip drawing objects entirely outside the view
+    clip_stack: Mutex<Vec<(i32, i32, i32, i32)>>, // Pushed scissor boxes, each already intersected with the one below
+    overlay_projection: Matrix4<f32>, // Camera-independent projection for screen-space objects
 }
 
 impl MasterGraphicsList {
@@ -12,15 +33,158 @@ impl MasterGraphicsList {
     pub fn new() -> Self {
         MasterGraphicsList {
             objects: Arc::new(RwLock::new(HashMap::new())),
+            name_collision_policy: NameCollisionPolicy::Overwrite,
+            pending_commands: Mutex::new(Vec::new()),
+            culling_enabled: true,
+            clip_stack: Mutex::new(Vec::new()),
+            overlay_projection: Matrix4::identity(),
         }
     }
 
-    /// Add an object to the list using its name as the key
-    pub fn add_object(&self, obj: Arc<RwLock<Generic2DGraphicsObject>>) {
-        let binding = obj.read().unwrap();
-        let name = binding.get_name();
+    /// Add an object to the list using its name as the key, resolving duplicate names with the current policy.
+    /// Returns the name the object was stored under.
+    pub fn add_object(&self, obj: Arc<RwLock<Generic2DGraphicsObject>>) -> EngineResult<String> {
         let mut objects = self.objects.write().unwrap();
-        objects.insert(name.to_owned(), obj.clone());
+        let name = obj.read().unwrap().get_name().to_owned();
+
+        if objects.contains_key(&name) {
+            match self.name_collision_policy {
+                NameCollisionPolicy::Error => {
+                    return Err(EngineError::DuplicateObject(name));
+                }
+                NameCollisionPolicy::AutoSuffix => {
+                    let unique_name = Self::unique_name_in(&objects, &name);
+                    obj.write().unwrap().set_name(unique_name.clone());
+                    objects.insert(unique_name.clone(), obj);
+                    return Ok(unique_name);
+                }
+                NameCollisionPolicy::Overwrite => {}
+            }
+        }
+
+        objects.insert(name.clone(), obj);
+        Ok(name)
+    }
+
+    /// Add several objects at once, returning the outcome of each add in order
+    pub fn add_objects<I>(&self, objs: I) -> Vec<EngineResult<String>>
+    where
+        I: IntoIterator<Item = Arc<RwLock<Generic2DGraphicsObject>>>,
+    {
+        objs.into_iter().map(|obj| self.add_object(obj)).collect()
+    }
+
+    /// Duplicate an existing object under a new name and position. The copy shares the source's GL buffers and shader.
+    /// Returns the name the copy was stored under.
+    pub fn clone_object(&self, source_name: &str, new_name: &str, new_position: Vector3<f32>) -> EngineResult<String> {
+        let source = self.get_object(source_name).ok_or_else(|| EngineError::ObjectNotFound(source_name.to_owned()))?;
+
+        let mut copy = source.read().unwrap().clone();
+        copy.set_name(new_name.to_owned());
+        copy.set_position(new_position);
+        copy.update_model_matrix();
+
+        self.add_object(Arc::new(RwLock::new(copy)))
+    }
+
+    /// Returns base_name if it is free, otherwise the first free base_name_N
+    pub fn generate_unique_name(&self, base_name: &str) -> String {
+        let objects = self.objects.read().unwrap();
+        Self::unique_name_in(&objects, base_name)
+    }
+
+    fn unique_name_in(objects: &HashMap<String, Arc<RwLock<Generic2DGraphicsObject>>>, base_name: &str) -> String {
+        if !objects.contains_key(base_name) {
+            return base_name.to_owned();
+        }
+
+        let mut counter = 1;
+        loop {
+            let candidate = format!("{}_{}", base_name, counter);
+            if !objects.contains_key(&candidate) {
+                return candidate;
+            }
+            counter += 1;
+        }
+    }
+
+    pub fn set_name_collision_policy(&mut self, policy: NameCollisionPolicy) {
+        self.name_collision_policy = policy;
+    }
+
+    pub fn get_name_collision_policy(&self) -> NameCollisionPolicy {
+        self.name_collision_policy
+    }
+
+    /// Turns view culling in draw_all on or off for the whole list, e.g. to compare frame times
+    pub fn set_culling_enabled(&mut self, culling_enabled: bool) {
+        self.culling_enabled = culling_enabled;
+    }
+
+    pub fn is_culling_enabled(&self) -> bool {
+        self.culling_enabled
+    }
+
+    /// Sets the projection screen-space objects are drawn with instead of the camera's.
+    /// The FrameworkController keeps it matched to the window every frame.
+    pub fn set_overlay_projection(&mut self, overlay_projection: Matrix4<f32>) {
+        self.overlay_projection = overlay_projection;
+    }
+
+    pub fn get_overlay_projection(&self) -> Matrix4<f32> {
+        self.overlay_projection
+    }
+
+    /// Limits all drawing to the rectangle, intersected with any rectangle already pushed, until the matching pop_clip_rect.
+    /// The projection places World rectangles. Needs a current GL context.
+    pub fn push_clip_rect(&self, clip_rect: ClipRect, projection_matrix: &Matrix4<f32>) {
+        let mut viewport = [0; 4];
+        unsafe { gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr()); }
+        let mut scissor = clip_rect.to_scissor(projection_matrix, (viewport[0], viewport[1], viewport[2], viewport[3]));
+
+        let mut clip_stack = self.clip_stack.lock().unwrap();
+        if let Some(&(outer_x, outer_y, outer_width, outer_height)) = clip_stack.last() {
+            let left = scissor.0.max(outer_x);
+            let bottom = scissor.1.max(outer_y);
+            let right = (scissor.0 + scissor.2).min(outer_x + outer_width);
+            let top = (scissor.1 + scissor.3).min(outer_y + outer_height);
+            scissor = (left, bottom, (right - left).max(0), (top - bottom).max(0));
+        }
+        clip_stack.push(scissor);
+
+        unsafe {
+            gl::Enable(gl::SCISSOR_TEST);
+            gl::Scissor(scissor.0, scissor.1, scissor.2, scissor.3);
+        }
+    }
+
+    /// Undoes the last push_clip_rect, going back to the rectangle below it or to unclipped drawing
+    pub fn pop_clip_rect(&self) {
+        let mut clip_stack = self.clip_stack.lock().unwrap();
+        if clip_stack.pop().is_none() {
+            log::warn!("pop_clip_rect called without a matching push_clip_rect");
+            return;
+        }
+        unsafe {
+            match clip_stack.last() {
+                Some(&(x, y, width, height)) => gl::Scissor(x, y, width, height),
+                None => gl::Disable(gl::SCISSOR_TEST),
+            }
+        }
+    }
+
+    /// Gives every named object the same clip rectangle (or removes it with None), e.g. all items of a scrollable list.
+    /// Returns how many of the objects were found.
+    pub fn set_group_clip_rect(&self, names: &[&str], clip_rect: Option<ClipRect>) -> usize {
+        let objects = self.objects.read().unwrap();
+        let mut found = 0;
+        for name in names {
+            if let Some(object) = objects.get(*name) {
+                object.write().unwrap().set_clip_rect(clip_rect);
+                found += 1;
+            }
+        }
+        found
     }
 
     /// Get an object by name
@@ -29,22 +193,228 @@ impl MasterGraphicsList {
         objects.get(name).cloned()
     }
 
-    /// Returns a pointer to the entire object list
+    /// Returns a snapshot of the handles to every object. The map itself is not kept locked.
+    pub fn get_handles(&self) -> Vec<Arc<RwLock<Generic2DGraphicsObject>>> {
+        let objects = self.objects.read().unwrap();
+        objects.values().cloned().collect()
+    }
+
+    /// Returns the names of every object in the list
+    pub fn get_names(&self) -> Vec<String> {
+        let objects = self.objects.read().unwrap();
+        objects.keys().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.read().unwrap().is_empty()
+    }
+
+    /// Visit every object with read access. Works on a snapshot of handles, so the visitor may safely add or remove objects.
+    pub fn for_each<F: FnMut(&Generic2DGraphicsObject)>(&self, mut visit: F) {
+        for obj in self.get_handles() {
+            visit(&obj.read().unwrap());
+        }
+    }
+
+    /// Visit every object with write access. Works on a snapshot of handles, so the visitor may safely add or remove objects.
+    pub fn for_each_mut<F: FnMut(&mut Generic2DGraphicsObject)>(&self, mut visit: F) {
+        for obj in self.get_handles() {
+            visit(&mut obj.write().unwrap());
+        }
+    }
+
+    /// Collect a value from every object the visitor returns Some for
+    pub fn filter_map<T, F: FnMut(&Generic2DGraphicsObject) -> Option<T>>(&self, mut visit: F) -> Vec<T> {
+        self.get_handles()
+            .iter()
+            .filter_map(|obj| visit(&obj.read().unwrap()))
+            .collect()
+    }
+
+    /// Returns a pointer to the entire object list.
+    /// Prefer get_handles or the visitor methods, holding this map's lock while locking objects invites deadlocks.
     pub fn get_objects(&self) -> Arc<RwLock<HashMap<String, Arc<RwLock<Generic2DGraphicsObject>>>>> {
         Arc::clone(&self.objects) // Return a clone of the Arc to allow shared access
     }
 
-    /// Draw all objects in the list, delta_time is used for animation
-    pub fn draw_all(&self, projection_matrix: &Matrix4<f32>, delta_time: f32) {
+    /// Queue an object to be added on the next flush
+    pub fn queue_add(&self, obj: Arc<RwLock<Generic2DGraphicsObject>>) {
+        self.queue_command(GraphicsListCommand::Add(obj));
+    }
+
+    /// Queue an object to be removed by name on the next flush
+    pub fn queue_remove(&self, name: &str) {
+        self.queue_command(GraphicsListCommand::Remove(name.to_owned()));
+    }
+
+    /// Queue a change to an object, applied with the object locked for writing on the next flush
+    pub fn queue_modify<F>(&self, name: &str, modify: F)
+    where
+        F: FnOnce(&mut Generic2DGraphicsObject) + Send + 'static,
+    {
+        self.queue_command(GraphicsListCommand::Modify(name.to_owned(), Box::new(modify)));
+    }
+
+    pub fn queue_command(&self, command: GraphicsListCommand) {
+        self.pending_commands.lock().unwrap().push(command);
+    }
+
+    /// Apply all queued commands in the order they were queued. Called once per frame by the FrameworkController before drawing.
+    pub fn flush_commands(&self) {
+        // Take the queue first so commands queued by a Modify closure land in the next flush instead of deadlocking
+        let commands = std::mem::take(&mut *self.pending_commands.lock().unwrap());
+
+        for command in commands {
+            match command {
+                GraphicsListCommand::Add(obj) => {
+                    if let Err(e) = self.add_object(obj) {
+                        log::error!("Queued add failed: {}", e);
+                    }
+                }
+                GraphicsListCommand::Remove(name) => self.remove_object(&name),
+                GraphicsListCommand::Modify(name, modify) => {
+                    match self.get_object(&name) {
+                        Some(obj) => modify(&mut obj.write().unwrap()),
+                        None => log::warn!("Queued modify failed: no object named '{}'", name),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Get every object whose bounds come within radius of a point
+    pub fn get_objects_in_radius(&self, point: Vector3<f32>, radius: f32) -> Vec<Arc<RwLock<Generic2DGraphicsObject>>> {
+        self.query_bounds(|min, max| {
+            // Distance from the point to the closest point on the bounds
+            let dx = (min.x - point.x).max(0.0).max(point.x - max.x);
+            let dy = (min.y - point.y).max(0.0).max(point.y - max.y);
+            dx * dx + dy * dy <= radius * radius
+        })
+    }
+
+    /// Get every object whose bounds overlap the rectangle between min and max
+    pub fn get_objects_in_rect(&self, rect_min: Vector3<f32>, rect_max: Vector3<f32>) -> Vec<Arc<RwLock<Generic2DGraphicsObject>>> {
+        self.query_bounds(|min, max| {
+            min.x <= rect_max.x && max.x >= rect_min.x && min.y <= rect_max.y && max.y >= rect_min.y
+        })
+    }
+
+    /// Get an object whose bounds contain the point, preferring the one whose center is closest
+    pub fn get_object_at_point(&self, point: Vector3<f32>) -> Option<Arc<RwLock<Generic2DGraphicsObject>>> {
+        self.query_bounds(|min, max| point.x >= min.x && point.x <= max.x && point.y >= min.y && point.y <= max.y)
+            .into_iter()
+            .min_by(|a, b| {
+                let distance_a = (a.read().unwrap().get_position().xy() - point.xy()).norm_squared();
+                let distance_b = (b.read().unwrap().get_position().xy() - point.xy()).norm_squared();
+                distance_a.total_cmp(&distance_b)
+            })
+    }
+
+    // Linear scan over the world bounds of every active object
+    fn query_bounds<F>(&self, matches: F) -> Vec<Arc<RwLock<Generic2DGraphicsObject>>>
+    where
+        F: Fn(&Vector3<f32>, &Vector3<f32>) -> bool,
+    {
         let objects = self.objects.read().unwrap();
-        for obj in objects.values() {
-            if let Ok(mut obj) = obj.write() { // Lock each object for writing (to update model matrix)
+        objects.values()
+            .filter(|obj| {
+                let obj = obj.read().unwrap();
+                if !obj.is_active() {
+                    return false;
+                }
+                let (min, max) = obj.get_world_bounds();
+                matches(&min, &max)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Advances every active object's animation and lifetime. Objects whose lifetime runs out or that leave
+    /// the view after being in it with despawn_offscreen set are removed. Runs headless too, nothing here touches GL.
+    pub fn update_all(&self, projection_matrix: &Matrix4<f32>, delta_time: f32) {
+        let mut despawned = Vec::new();
+
+        let objects = self.objects.read().unwrap();
+        for (name, object) in objects.iter() {
+            if let Ok(mut obj) = object.write() {
+                if !obj.is_active() {
+                    continue;
+                }
+                let projection_matrix = if obj.is_screen_space() { &self.overlay_projection } else { projection_matrix };
                 obj.update_animation(delta_time);
+                if obj.update_lifetime(delta_time) || obj.has_left_view(projection_matrix) {
+                    despawned.push(name.clone());
+                }
+            }
+        }
+        drop(objects);
+
+        self.remove_many(despawned);
+    }
+
+    /// Draw all objects in the list, call update_all first to advance them.
+    /// Objects are drawn back to front by layer, then position.z, then name, with the depth test off for the pass.
+    /// Objects entirely outside the view are skipped unless they are set to always render.
+    pub fn draw_all(&self, projection_matrix: &Matrix4<f32>) {
+        self.draw_all_with_program(projection_matrix, None);
+    }
+
+    /// draw_all, but with every object drawn by override_program instead of its own shader when one is given
+    pub(crate) fn draw_all_with_program(&self, projection_matrix: &Matrix4<f32>, override_program: Option<GLuint>) {
+        let mut drawable = Vec::new();
+
+        let objects = self.objects.read().unwrap();
+        for (name, object) in objects.iter() {
+            if let Ok(obj) = object.read() {
+                let screen_space = obj.is_screen_space();
+                let projection_matrix = if screen_space { &self.overlay_projection } else { projection_matrix };
+                let culled = self.culling_enabled && !obj.get_always_render() && !obj.is_within_view(projection_matrix);
+                if obj.is_visible() && !culled {
+                    drawable.push((screen_space, obj.get_layer(), obj.get_position().z, name.clone(), Arc::clone(object)));
+                }
+            }
+        }
+        drop(objects);
+
+        // World before overlay, then lower layers first, then lower z, then by name so objects sharing a layer and z don't flicker between frames
+        drawable.sort_by(|(screen_a, layer_a, z_a, name_a, _), (screen_b, layer_b, z_b, name_b, _)| {
+            screen_a.cmp(screen_b).then_with(|| layer_a.cmp(layer_b)).then_with(|| z_a.total_cmp(z_b)).then_with(|| name_a.cmp(name_b))
+        });
+
+        // Draw order alone decides what ends up on top. With the depth test on, a later object at the same z
+        // would fail it and vanish behind an earlier one.
+        let depth_test_was_enabled = unsafe { gl::IsEnabled(gl::DEPTH_TEST) == gl::TRUE };
+        unsafe { gl::Disable(gl::DEPTH_TEST); }
+
+        for (screen_space, _, _, _, object) in drawable {
+            let projection_matrix = if screen_space { &self.overlay_projection } else { projection_matrix };
+            if let Ok(mut obj) = object.write() { // Lock each object for writing (to update model matrix)
                 obj.update_model_matrix(); // Update the model matrix first
-                obj.apply_transform(projection_matrix); // Apply the projection matrix
-                obj.draw();
+                let clip_rect = obj.get_clip_rect();
+                if let Some(clip_rect) = clip_rect {
+                    self.push_clip_rect(clip_rect, projection_matrix);
+                }
+                match override_program {
+                    Some(program) => obj.draw_with_program(program, projection_matrix),
+                    None => {
+                        obj.apply_transform(projection_matrix); // Apply the projection matrix
+                        obj.apply_uniforms(); // Custom uniforms set by the game
+                        obj.draw();
+                    }
+                }
+                if clip_rect.is_some() {
+                    self.pop_clip_rect();
+                }
             }
         }
+
+        if depth_test_was_enabled {
+            unsafe { gl::Enable(gl::DEPTH_TEST); }
+        }
     }
 
     /// If we want to print ALL info for ALL objects
@@ -63,6 +433,18 @@ impl MasterGraphicsList {
         objects.remove(name);
     }
 
+    /// Remove several objects by name
+    pub fn remove_many<I, S>(&self, names: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut objects = self.objects.write().unwrap();
+        for name in names {
+            objects.remove(name.as_ref());
+        }
+    }
+
     /// Remove all objects from the list
     pub fn remove_all(&self) {
         let mut objects = self.objects.write().unwrap();
@@ -0,0 +1,114 @@
+use std::sync::{Arc, RwLock};
+use nalgebra::Vector3;
+
+use crate::framework::graphics::internal_object::graphics_object::Generic2DGraphicsObject;
+use super::master_graphics_list::MasterGraphicsList;
+
+struct PooledObject {
+    name: String,
+    object: Arc<RwLock<Generic2DGraphicsObject>>,
+    in_use: bool,
+}
+
+/// Pre-creates copies of a template object and parks them (inactive and invisible) in the MasterGraphicsList,
+/// so bursts of bullets or particles don't create GL objects mid-frame.
+pub struct ObjectPool {
+    template: Generic2DGraphicsObject,
+    instances: Vec<PooledObject>,
+}
+
+impl ObjectPool {
+    /// Creates `size` copies of the template named "{template name}_pool_{index}" and adds them to the graphics list.
+    /// The copies share the template's GL buffers and shader.
+    pub fn new(template: Generic2DGraphicsObject, size: usize, graphics_list: &MasterGraphicsList) -> Self {
+        let mut instances = Vec::with_capacity(size);
+        for index in 0..size {
+            let name = format!("{}_pool_{}", template.get_name(), index);
+            let object = Arc::new(RwLock::new(Self::parked_copy(&template, &name)));
+            if let Err(e) = graphics_list.add_object(object.clone()) {
+                log::error!("Could not add pooled object '{}': {}", name, e);
+            }
+            instances.push(PooledObject { name, object, in_use: false });
+        }
+
+        ObjectPool {
+            template,
+            instances,
+        }
+    }
+
+    // A fresh copy of the template, hidden and disabled until it is handed out
+    fn parked_copy(template: &Generic2DGraphicsObject, name: &str) -> Generic2DGraphicsObject {
+        let mut copy = template.clone();
+        copy.set_name(name.to_owned());
+        copy.set_visible(false);
+        copy.set_active(false);
+        copy
+    }
+
+    /// Hands out a free instance reset to the template's state at the given position, or None if the pool is exhausted.
+    /// Instances the list removed on its own (an expired lifetime, despawn_offscreen) count as free again.
+    pub fn acquire(&mut self, position: Vector3<f32>, graphics_list: &MasterGraphicsList) -> Option<Arc<RwLock<Generic2DGraphicsObject>>> {
+        let instance = self.instances.iter_mut().find(|instance| Self::is_free(instance, graphics_list))?;
+
+        {
+            let mut object = instance.object.write().unwrap();
+            *object = self.template.clone();
+            object.set_name(instance.name.clone());
+            object.set_position(position);
+            object.update_model_matrix();
+        }
+        instance.in_use = true;
+
+        // The instance may have been removed from the list in the meantime (e.g. by an expired lifetime)
+        if !Self::is_in_list(instance, graphics_list) {
+            if let Err(e) = graphics_list.add_object(instance.object.clone()) {
+                log::error!("Could not re-add pooled object '{}': {}", instance.name, e);
+            }
+        }
+
+        Some(instance.object.clone())
+    }
+
+    /// Returns an instance to the pool, hiding and disabling it. Returns false if the name doesn't belong to this pool.
+    pub fn release(&mut self, name: &str) -> bool {
+        match self.instances.iter_mut().find(|instance| instance.name == name) {
+            Some(instance) => {
+                let mut object = instance.object.write().unwrap();
+                object.set_visible(false);
+                object.set_active(false);
+                instance.in_use = false;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns every instance to the pool
+    pub fn release_all(&mut self) {
+        for instance in self.instances.iter_mut() {
+            let mut object = instance.object.write().unwrap();
+            object.set_visible(false);
+            object.set_active(false);
+            instance.in_use = false;
+        }
+    }
+
+    /// Free instances, including ones the list has removed since they were acquired
+    pub fn available(&self, graphics_list: &MasterGraphicsList) -> usize {
+        self.instances.iter().filter(|instance| Self::is_free(instance, graphics_list)).count()
+    }
+
+    fn is_free(instance: &PooledObject, graphics_list: &MasterGraphicsList) -> bool {
+        !instance.in_use || !Self::is_in_list(instance, graphics_list)
+    }
+
+    // Whether the list still holds this very instance, not just something else under its name
+    fn is_in_list(instance: &PooledObject, graphics_list: &MasterGraphicsList) -> bool {
+        graphics_list.get_object(&instance.name).is_some_and(|object| Arc::ptr_eq(&object, &instance.object))
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.instances.len()
+    }
+}
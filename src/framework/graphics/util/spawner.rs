@@ -0,0 +1,138 @@
+use std::sync::{Arc, RwLock};
+use nalgebra::Vector3;
+use serde::{Deserialize, Serialize};
+
+use crate::framework::graphics::internal_object::graphics_object::Generic2DGraphicsObject;
+use super::master_graphics_list::MasterGraphicsList;
+
+#[derive(Serialize, Debug, Clone, Deserialize)]
+pub struct SpawnerConfig {
+    pub interval: f32, // Seconds between spawns
+    pub batch_size: usize, // Objects spawned each interval
+    pub count: usize, // Total objects to spawn, 0 for unlimited
+    pub max_alive: usize, // Spawned objects allowed in the list at once, 0 for unlimited
+    pub pattern: String, // "point", "area" (random inside a rectangle) or "circle" (evenly around a ring)
+    pub origin: [f32; 3],
+    pub extents: [f32; 2], // Half width/height for "area", radius in the first element for "circle"
+}
+
+pub struct Spawner {
+    config: SpawnerConfig,
+    template: Generic2DGraphicsObject,
+    elapsed_time: f32,
+    spawned_total: usize,
+    alive: Vec<String>, // Names of spawned objects still in the list
+    enabled: bool,
+}
+
+impl Spawner {
+    pub fn new(config: SpawnerConfig, template: Generic2DGraphicsObject) -> Self {
+        Spawner {
+            config,
+            template,
+            elapsed_time: 0.0,
+            spawned_total: 0,
+            alive: Vec::new(),
+            enabled: true,
+        }
+    }
+
+    /// Advances the spawn timer and spawns copies of the template into the list when due
+    pub fn update(&mut self, delta_time: f32, graphics_list: &MasterGraphicsList) {
+        // Forget objects that have been removed (killed, despawned) since the last update
+        self.alive.retain(|name| graphics_list.get_object(name).is_some());
+
+        if !self.enabled || self.config.interval <= 0.0 || self.is_finished() {
+            return;
+        }
+
+        self.elapsed_time += delta_time;
+        while self.elapsed_time >= self.config.interval {
+            self.elapsed_time -= self.config.interval;
+            self.spawn_batch(graphics_list);
+        }
+    }
+
+    fn spawn_batch(&mut self, graphics_list: &MasterGraphicsList) {
+        for batch_index in 0..self.config.batch_size {
+            if self.is_finished() {
+                return;
+            }
+            if self.config.max_alive != 0 && self.alive.len() >= self.config.max_alive {
+                return;
+            }
+
+            let position = self.spawn_position(batch_index);
+            let mut object = self.template.clone();
+            object.set_name(graphics_list.generate_unique_name(self.template.get_name()));
+            object.set_position(position);
+            object.update_model_matrix();
+
+            match graphics_list.add_object(Arc::new(RwLock::new(object))) {
+                Ok(name) => {
+                    self.alive.push(name);
+                    self.spawned_total += 1;
+                }
+                Err(e) => log::error!("Spawner could not add object: {}", e),
+            }
+        }
+    }
+
+    fn spawn_position(&self, batch_index: usize) -> Vector3<f32> {
+        let [x, y, z] = self.config.origin;
+        let origin = Vector3::new(x, y, z);
+
+        match self.config.pattern.as_str() {
+            "area" => {
+                let [half_width, half_height] = self.config.extents;
+                origin + Vector3::new(random_offset(half_width), random_offset(half_height), 0.0)
+            }
+            "circle" => {
+                let radius = self.config.extents[0];
+                let angle = batch_index as f32 / self.config.batch_size.max(1) as f32 * 2.0 * std::f32::consts::PI;
+                origin + Vector3::new(angle.cos() * radius, angle.sin() * radius, 0.0)
+            }
+            _ => origin, // "point" or unrecognized pattern
+        }
+    }
+
+    /// Returns true once the spawner has spawned its full count
+    pub fn is_finished(&self) -> bool {
+        self.config.count != 0 && self.spawned_total >= self.config.count
+    }
+
+    /// Names of spawned objects that are still in the list
+    pub fn get_alive(&self) -> &[String] {
+        &self.alive
+    }
+
+    pub fn get_config(&self) -> SpawnerConfig {
+        self.config.clone()
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Starts the spawner over, forgetting how many objects it has spawned
+    pub fn reset(&mut self) {
+        self.elapsed_time = 0.0;
+        self.spawned_total = 0;
+        self.alive.clear();
+    }
+}
+
+// A random offset within +-extent. Extents come from user JSON, so a negative one counts as its size
+// and a zero, infinite or NaN one as no spread at all rather than an invalid range.
+fn random_offset(extent: f32) -> f32 {
+    use rand::Rng;
+    let extent = extent.abs();
+    if extent == 0.0 || !extent.is_finite() {
+        return 0.0;
+    }
+    rand::rng().random_range(-extent..=extent)
+}
@@ -0,0 +1,98 @@
+use std::{collections::HashMap, fs};
+
+use super::error::{EngineError, EngineResult};
+
+/// String tables per language, loaded from flat JSON objects of key -> text.
+/// Text may contain {placeholders} filled in by get_with_args.
+pub struct Localization {
+    languages: HashMap<String, HashMap<String, String>>,
+    current_language: String,
+    fallback_language: String, // Looked up when the current language is missing a key
+}
+
+impl Localization {
+    pub fn new(default_language: &str) -> Self {
+        Localization {
+            languages: HashMap::new(),
+            current_language: default_language.to_owned(),
+            fallback_language: default_language.to_owned(),
+        }
+    }
+
+    /// Loads (or replaces) one language's string table from a JSON file
+    pub fn load_language(&mut self, language: &str, path: &str) -> EngineResult<()> {
+        let contents = fs::read_to_string(path).map_err(|e| EngineError::io(path, e))?;
+        self.load_language_from_str(language, &contents, path)
+    }
+
+    /// Loads (or replaces) one language's string table from JSON text. source names it in parse errors.
+    pub fn load_language_from_str(&mut self, language: &str, contents: &str, source: &str) -> EngineResult<()> {
+        let table: HashMap<String, String> = serde_json::from_str(contents).map_err(|e| EngineError::parse(source, e))?;
+        self.languages.insert(language.to_owned(), table);
+        Ok(())
+    }
+
+    /// Loads every .json file in a directory as a language named after the file (en.json -> "en")
+    pub fn load_languages_from_directory(&mut self, dir_path: &str) -> EngineResult<()> {
+        let paths = fs::read_dir(dir_path).map_err(|e| EngineError::io(dir_path, e))?;
+
+        for path in paths {
+            let entry = path.map_err(|e| EngineError::io(dir_path, e))?;
+            let full_path = entry.path();
+
+            if full_path.is_file() && full_path.extension().is_some_and(|extension| extension == "json") {
+                let path_str = full_path.to_str().ok_or_else(|| EngineError::InvalidFileName(full_path.display().to_string()))?;
+                if let Some(language) = full_path.file_stem().and_then(|stem| stem.to_str()) {
+                    self.load_language(language, path_str)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Switches the language used for lookups. Fails if no table has been loaded for it.
+    pub fn set_language(&mut self, language: &str) -> EngineResult<()> {
+        if !self.languages.contains_key(language) {
+            return Err(EngineError::LanguageNotLoaded(language.to_owned()));
+        }
+        self.current_language = language.to_owned();
+        Ok(())
+    }
+
+    pub fn get_language(&self) -> &str {
+        &self.current_language
+    }
+
+    pub fn set_fallback_language(&mut self, language: &str) {
+        self.fallback_language = language.to_owned();
+    }
+
+    /// Languages that have a string table loaded
+    pub fn get_languages(&self) -> Vec<String> {
+        self.languages.keys().cloned().collect()
+    }
+
+    /// Looks up a key in the current language, then the fallback language. Returns the key itself if neither has it,
+    /// so missing strings are obvious on screen rather than blank.
+    pub fn get(&self, key: &str) -> String {
+        [&self.current_language, &self.fallback_language]
+            .iter()
+            .find_map(|language| self.languages.get(*language).and_then(|table| table.get(key)))
+            .cloned()
+            .unwrap_or_else(|| key.to_owned())
+    }
+
+    /// Looks up a key and replaces each {name} placeholder with its argument
+    pub fn get_with_args(&self, key: &str, args: &[(&str, &str)]) -> String {
+        let mut text = self.get(key);
+        for (name, value) in args {
+            text = text.replace(&format!("{{{}}}", name), value);
+        }
+        text
+    }
+
+    pub fn has_key(&self, key: &str) -> bool {
+        self.languages.get(&self.current_language).is_some_and(|table| table.contains_key(key))
+    }
+}
@@ -0,0 +1,93 @@
+use std::{collections::HashMap, fs::{File, OpenOptions}, io::Write, sync::Mutex, time::{SystemTime, UNIX_EPOCH}};
+use log::{LevelFilter, Log, Metadata, Record};
+
+use super::error::{EngineError, EngineResult};
+
+/// Leveled logger behind the `log` facade, with per-module levels and optional file output.
+/// Install it once at startup with init(), after which the engine's log messages are routed through it.
+pub struct EngineLogger {
+    default_level: LevelFilter,
+    module_levels: HashMap<String, LevelFilter>, // Module path prefix -> level, e.g. "rusted_open::framework::graphics"
+    log_file: Option<Mutex<File>>,
+    print_to_console: bool,
+}
+
+impl EngineLogger {
+    pub fn new(default_level: LevelFilter) -> Self {
+        EngineLogger {
+            default_level,
+            module_levels: HashMap::new(),
+            log_file: None,
+            print_to_console: true,
+        }
+    }
+
+    /// Overrides the level for a module and everything beneath it. The most specific match wins.
+    pub fn set_module_level(&mut self, module: &str, level: LevelFilter) {
+        self.module_levels.insert(module.to_owned(), level);
+    }
+
+    /// Also appends every log line to the given file
+    pub fn set_log_file(&mut self, path: &str) -> EngineResult<()> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| EngineError::io(path, e))?;
+        self.log_file = Some(Mutex::new(file));
+        Ok(())
+    }
+
+    pub fn set_print_to_console(&mut self, print_to_console: bool) {
+        self.print_to_console = print_to_console;
+    }
+
+    /// Installs this as the global logger. Can only succeed once per process.
+    pub fn init(self) -> EngineResult<()> {
+        let max_level = self.module_levels.values().copied().fold(self.default_level, Ord::max);
+        log::set_boxed_logger(Box::new(self)).map_err(|_| EngineError::LoggerAlreadyInstalled)?;
+        log::set_max_level(max_level);
+        Ok(())
+    }
+
+    fn level_for(&self, target: &str) -> LevelFilter {
+        self.module_levels
+            .iter()
+            .filter(|(module, _)| target == module.as_str() || target.starts_with(&format!("{}::", module)))
+            .max_by_key(|(module, _)| module.len())
+            .map(|(_, level)| *level)
+            .unwrap_or(self.default_level)
+    }
+}
+
+impl Log for EngineLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level_for(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs_f64()).unwrap_or(0.0);
+        let line = format!("[{:.3}] {:<5} {}: {}", timestamp, record.level(), record.target(), record.args());
+
+        if self.print_to_console {
+            println!("{}", line);
+        }
+        if let Some(log_file) = &self.log_file {
+            if let Ok(mut file) = log_file.lock() {
+                let _ = writeln!(file, "{}", line);
+            }
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(log_file) = &self.log_file {
+            if let Ok(mut file) = log_file.lock() {
+                let _ = file.flush();
+            }
+        }
+    }
+}
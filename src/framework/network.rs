@@ -0,0 +1,7 @@
+pub mod transport;
+pub mod udp;
+#[cfg(feature = "websocket")]
+pub mod websocket;
+pub mod replication;
+pub mod interpolation;
+pub mod rollback;
\ No newline at end of file
@@ -0,0 +1,109 @@
+use std::{collections::{HashMap, VecDeque}, f32::consts::PI};
+
+use crate::framework::graphics::util::master_graphics_list::MasterGraphicsList;
+
+use super::replication::{apply_object_snapshot, ObjectSnapshot, Snapshot};
+
+/// Smooths replicated objects by showing them slightly in the past, between the two snapshots around that moment.
+/// When snapshots stop arriving the object keeps moving at its last velocity for a short while, then holds still.
+///
+/// Feed it the snapshots from Replicator::decode instead of applying them directly, then call update every frame.
+pub struct Interpolator {
+    time: f32, // Local clock, advanced by update
+    delay: f32, // How far in the past objects are shown, usually two or three snapshot intervals
+    max_extrapolation: f32, // How long to keep predicting after the newest snapshot before holding still
+    buffers: HashMap<String, VecDeque<(f32, ObjectSnapshot)>>, // Object name -> (receive time, state), oldest first
+}
+
+impl Interpolator {
+    pub fn new(delay: f32, max_extrapolation: f32) -> Self {
+        Interpolator {
+            time: 0.0,
+            delay,
+            max_extrapolation,
+            buffers: HashMap::new(),
+        }
+    }
+
+    pub fn set_delay(&mut self, delay: f32) {
+        self.delay = delay;
+    }
+
+    pub fn set_max_extrapolation(&mut self, max_extrapolation: f32) {
+        self.max_extrapolation = max_extrapolation;
+    }
+
+    /// Records a received snapshot at the current time
+    pub fn push(&mut self, snapshot: &Snapshot) {
+        for object in &snapshot.objects {
+            self.buffers.entry(object.name.clone()).or_default().push_back((self.time, object.clone()));
+        }
+    }
+
+    /// Forgets an object, e.g. once it has been removed from the list
+    pub fn remove(&mut self, name: &str) {
+        self.buffers.remove(name);
+    }
+
+    pub fn clear(&mut self) {
+        self.buffers.clear();
+    }
+
+    /// Advances the clock and writes every buffered object's state at (now - delay) into the list
+    pub fn update(&mut self, delta_time: f32, graphics_list: &MasterGraphicsList) {
+        self.time += delta_time;
+        let render_time = self.time - self.delay;
+
+        for buffer in self.buffers.values_mut() {
+            // Keep one snapshot at or before render_time to interpolate from, and drop anything older
+            while buffer.len() > 2 && buffer[1].0 <= render_time {
+                buffer.pop_front();
+            }
+
+            if let Some(state) = Self::sample(buffer, render_time, self.max_extrapolation) {
+                apply_object_snapshot(&state, graphics_list);
+            }
+        }
+    }
+
+    fn sample(buffer: &VecDeque<(f32, ObjectSnapshot)>, render_time: f32, max_extrapolation: f32) -> Option<ObjectSnapshot> {
+        let (first_time, first) = buffer.front()?;
+        if buffer.len() == 1 || render_time <= *first_time {
+            return Some(first.clone());
+        }
+
+        let (from_time, from, to_time, to) = match buffer.iter().zip(buffer.iter().skip(1)).find(|(_, (to_time, _))| render_time <= *to_time) {
+            Some(((from_time, from), (to_time, to))) => (*from_time, from, *to_time, to),
+            None => {
+                // Past the newest snapshot, so extrapolate along the last two
+                let (to_time, to) = &buffer[buffer.len() - 1];
+                let (from_time, from) = &buffer[buffer.len() - 2];
+                (*from_time, from, *to_time, to)
+            }
+        };
+
+        let span = (to_time - from_time).max(f32::EPSILON);
+        let max_t = 1.0 + max_extrapolation / span;
+        let t = ((render_time - from_time) / span).clamp(0.0, max_t);
+
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+        // Turn the short way round, so 350 -> 10 degrees doesn't spin through 180
+        let mut rotation_delta = (to.rotation - from.rotation) % (2.0 * PI);
+        if rotation_delta > PI {
+            rotation_delta -= 2.0 * PI;
+        } else if rotation_delta < -PI {
+            rotation_delta += 2.0 * PI;
+        }
+
+        Some(ObjectSnapshot {
+            name: to.name.clone(),
+            position: [lerp(from.position[0], to.position[0]), lerp(from.position[1], to.position[1]), lerp(from.position[2], to.position[2])],
+            rotation: from.rotation + rotation_delta * t,
+            scale: [lerp(from.scale[0], to.scale[0]), lerp(from.scale[1], to.scale[1])],
+            visible: if t < 1.0 { from.visible } else { to.visible },
+            current_frame: if t < 1.0 { from.current_frame } else { to.current_frame },
+            flip_x: if t < 1.0 { from.flip_x } else { to.flip_x },
+            flip_y: if t < 1.0 { from.flip_y } else { to.flip_y },
+        })
+    }
+}
@@ -0,0 +1,367 @@
+use std::collections::HashMap;
+
+use nalgebra::Vector3;
+
+use crate::framework::{events::fixed_timestep::FixedTimestep, graphics::util::master_graphics_list::MasterGraphicsList};
+
+use super::{transport::{Channel, PeerId, Transport}, udp::MAX_MESSAGE_SIZE};
+
+// First byte of every replication message, so they can share a transport with the game's own messages
+pub const REPLICATION_MESSAGE_TAG: u8 = 0xA5;
+
+const MESSAGE_HEADER_SIZE: usize = 7; // Tag, u32 tick and u16 object count
+
+const FLAG_VISIBLE: u8 = 1;
+const FLAG_FLIP_X: u8 = 2;
+const FLAG_FLIP_Y: u8 = 4;
+const FLAG_HAS_FRAME: u8 = 8;
+
+/// The replicated state of one object
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectSnapshot {
+    pub name: String,
+    pub position: [f32; 3],
+    pub rotation: f32,
+    pub scale: [f32; 2],
+    pub visible: bool,
+    pub current_frame: Option<usize>,
+    pub flip_x: bool,
+    pub flip_y: bool,
+}
+
+/// Every object one side owns, as of one replication tick
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub tick: u32,
+    pub objects: Vec<ObjectSnapshot>,
+}
+
+/// Who is allowed to change an object. Only the owner's snapshots are applied, so a client can't move objects it doesn't own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Owner {
+    /// This side is the authority and sends the object's state
+    Local,
+    /// The given peer is the authority, and only its snapshots are applied
+    Remote(PeerId),
+}
+
+/// Sends the state of locally owned objects at a fixed tick rate, and applies snapshots of remotely owned ones.
+/// Snapshots go over the unreliable channel, since a lost one is superseded by the next tick anyway.
+/// They are binary encoded and split over as many messages as it takes to fit a UDP packet each; every message
+/// carries the tick and whole objects, so it can be applied on its own when others are lost.
+pub struct Replicator {
+    registered: HashMap<String, Owner>,
+    tick_timestep: FixedTimestep,
+    tick: u32,
+    last_received_tick: HashMap<PeerId, u32>, // Older snapshots arriving late are ignored
+}
+
+impl Replicator {
+    /// tick_rate is how many snapshots per second are sent
+    pub fn new(tick_rate: f32) -> Self {
+        Replicator {
+            registered: HashMap::new(),
+            tick_timestep: FixedTimestep::new(tick_rate, 1),
+            tick: 0,
+            last_received_tick: HashMap::new(),
+        }
+    }
+
+    pub fn set_tick_rate(&mut self, tick_rate: f32) {
+        self.tick_timestep.set_hz(tick_rate);
+    }
+
+    pub fn register(&mut self, name: &str, owner: Owner) {
+        self.registered.insert(name.to_owned(), owner);
+    }
+
+    pub fn unregister(&mut self, name: &str) {
+        self.registered.remove(name);
+    }
+
+    pub fn get_owner(&self, name: &str) -> Option<Owner> {
+        self.registered.get(name).copied()
+    }
+
+    /// Hands ownership of every object a peer owned back to this side, e.g. when it disconnects
+    pub fn reclaim_ownership(&mut self, peer: PeerId) {
+        for owner in self.registered.values_mut() {
+            if *owner == Owner::Remote(peer) {
+                *owner = Owner::Local;
+            }
+        }
+        self.last_received_tick.remove(&peer);
+    }
+
+    pub fn get_tick(&self) -> u32 {
+        self.tick
+    }
+
+    /// Captures the locally owned objects that exist in the list
+    pub fn capture(&self, graphics_list: &MasterGraphicsList) -> Snapshot {
+        let objects = self.registered
+            .iter()
+            .filter(|(_, owner)| **owner == Owner::Local)
+            .filter_map(|(name, _)| graphics_list.get_object(name))
+            .map(|object| {
+                let object = object.read().unwrap();
+                let position = object.get_position();
+                let scale = object.get_scale();
+                ObjectSnapshot {
+                    name: object.get_name().to_owned(),
+                    position: [position.x, position.y, position.z],
+                    rotation: object.get_rotation(),
+                    scale: [scale.x, scale.y],
+                    visible: object.is_visible(),
+                    current_frame: object.get_atlas_config().map(|atlas_config| atlas_config.current_frame),
+                    flip_x: object.get_flip_x(),
+                    flip_y: object.get_flip_y(),
+                }
+            })
+            .collect();
+
+        Snapshot { tick: self.tick, objects }
+    }
+
+    /// Call once per frame. Broadcasts a snapshot to every peer whenever a tick is due.
+    pub fn update<T: Transport>(&mut self, delta_time: f32, transport: &mut T, graphics_list: &MasterGraphicsList) {
+        if self.tick_timestep.accumulate(delta_time) == 0 {
+            return;
+        }
+
+        let snapshot = self.capture(graphics_list);
+        self.tick = self.tick.wrapping_add(1);
+        if snapshot.objects.is_empty() {
+            return;
+        }
+
+        for message in encode_snapshot(&snapshot, MAX_MESSAGE_SIZE) {
+            transport.broadcast(Channel::Unreliable, &message);
+        }
+    }
+
+    /// Decodes a message if it is a replication snapshot newer than the last one from that peer.
+    /// Returns None for anything else, so the game can handle its own messages.
+    pub fn decode(&mut self, peer: PeerId, data: &[u8]) -> Option<Snapshot> {
+        let (&tag, encoded) = data.split_first()?;
+        if tag != REPLICATION_MESSAGE_TAG {
+            return None;
+        }
+
+        let Some(snapshot) = decode_snapshot(encoded) else {
+            log::warn!("Dropping malformed snapshot from {}", peer);
+            return None;
+        };
+
+        // The other messages of the latest tick still count, a large snapshot is split over several
+        if self.last_received_tick.get(&peer).is_some_and(|&last| snapshot.tick < last) {
+            return None;
+        }
+        self.last_received_tick.insert(peer, snapshot.tick);
+
+        // Ownership rules: drop anything the sender isn't the authority for
+        let objects = snapshot.objects.into_iter().filter(|object| self.registered.get(&object.name) == Some(&Owner::Remote(peer))).collect();
+        Some(Snapshot { tick: snapshot.tick, objects })
+    }
+
+    /// Applies a received message to the list straight away. Returns whether it was a replication message.
+    pub fn handle_message(&mut self, peer: PeerId, data: &[u8], graphics_list: &MasterGraphicsList) -> bool {
+        match self.decode(peer, data) {
+            Some(snapshot) => {
+                for object_snapshot in &snapshot.objects {
+                    apply_object_snapshot(object_snapshot, graphics_list);
+                }
+                true
+            }
+            None => data.first() == Some(&REPLICATION_MESSAGE_TAG),
+        }
+    }
+}
+
+/// Encodes a snapshot into messages of at most max_size bytes: the tag, the tick, an object count, then per object
+/// a u16 name length, the name, position, rotation and scale as f32s, a flags byte and the frame as a u32 if it has one.
+/// Everything is little-endian. An object too large to fit a message on its own is left out.
+fn encode_snapshot(snapshot: &Snapshot, max_size: usize) -> Vec<Vec<u8>> {
+    let start_message = || {
+        let mut message = Vec::with_capacity(max_size);
+        message.push(REPLICATION_MESSAGE_TAG);
+        message.extend_from_slice(&snapshot.tick.to_le_bytes());
+        message.extend_from_slice(&0u16.to_le_bytes()); // Object count, filled in once the message is full
+        message
+    };
+    let finish_message = |mut message: Vec<u8>, count: u16| {
+        message[5..7].copy_from_slice(&count.to_le_bytes());
+        message
+    };
+
+    let mut messages = Vec::new();
+    let mut message = start_message();
+    let mut count: u16 = 0;
+    let mut encoded_object = Vec::new();
+    for object in &snapshot.objects {
+        encoded_object.clear();
+        encode_object(object, &mut encoded_object);
+        if MESSAGE_HEADER_SIZE + encoded_object.len() > max_size {
+            log::warn!("Not replicating {}, its name is too long to fit a message", object.name);
+            continue;
+        }
+
+        if message.len() + encoded_object.len() > max_size || count == u16::MAX {
+            messages.push(finish_message(message, count));
+            message = start_message();
+            count = 0;
+        }
+        message.extend_from_slice(&encoded_object);
+        count += 1;
+    }
+    if count > 0 {
+        messages.push(finish_message(message, count));
+    }
+    messages
+}
+
+fn encode_object(object: &ObjectSnapshot, out: &mut Vec<u8>) {
+    let name = &object.name.as_bytes()[..object.name.len().min(u16::MAX as usize)];
+    out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    out.extend_from_slice(name);
+    for value in object.position.iter().chain([object.rotation].iter()).chain(object.scale.iter()) {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+
+    let mut flags = 0;
+    if object.visible { flags |= FLAG_VISIBLE; }
+    if object.flip_x { flags |= FLAG_FLIP_X; }
+    if object.flip_y { flags |= FLAG_FLIP_Y; }
+    if object.current_frame.is_some() { flags |= FLAG_HAS_FRAME; }
+    out.push(flags);
+    if let Some(current_frame) = object.current_frame {
+        out.extend_from_slice(&(current_frame as u32).to_le_bytes());
+    }
+}
+
+/// Reverses encode_snapshot for one message with the tag already stripped. None if it is truncated or malformed.
+fn decode_snapshot(encoded: &[u8]) -> Option<Snapshot> {
+    let mut cursor = 0;
+    let mut read_bytes = |length: usize| -> Option<&[u8]> {
+        let bytes = encoded.get(cursor..cursor + length)?;
+        cursor += length;
+        Some(bytes)
+    };
+
+    let tick = u32::from_le_bytes(read_bytes(4)?.try_into().ok()?);
+    let count = u16::from_le_bytes(read_bytes(2)?.try_into().ok()?);
+    let mut objects = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let name_length = u16::from_le_bytes(read_bytes(2)?.try_into().ok()?) as usize;
+        let name = String::from_utf8(read_bytes(name_length)?.to_vec()).ok()?;
+        let mut values = [0.0f32; 6];
+        for value in values.iter_mut() {
+            *value = f32::from_le_bytes(read_bytes(4)?.try_into().ok()?);
+        }
+        let flags = read_bytes(1)?[0];
+        let current_frame = if flags & FLAG_HAS_FRAME != 0 {
+            Some(u32::from_le_bytes(read_bytes(4)?.try_into().ok()?) as usize)
+        } else {
+            None
+        };
+
+        objects.push(ObjectSnapshot {
+            name,
+            position: [values[0], values[1], values[2]],
+            rotation: values[3],
+            scale: [values[4], values[5]],
+            visible: flags & FLAG_VISIBLE != 0,
+            current_frame,
+            flip_x: flags & FLAG_FLIP_X != 0,
+            flip_y: flags & FLAG_FLIP_Y != 0,
+        });
+    }
+
+    if read_bytes(1).is_some() {
+        return None; // Trailing bytes, not something encode_snapshot wrote
+    }
+    Some(Snapshot { tick, objects })
+}
+
+/// Writes one object's replicated state into the list, if the object exists
+pub fn apply_object_snapshot(object_snapshot: &ObjectSnapshot, graphics_list: &MasterGraphicsList) {
+    let Some(object) = graphics_list.get_object(&object_snapshot.name) else {
+        return;
+    };
+
+    let mut object = object.write().unwrap();
+    let [x, y, z] = object_snapshot.position;
+    object.set_position(Vector3::new(x, y, z));
+    object.set_rotation(object_snapshot.rotation);
+    object.set_scale_xy(object_snapshot.scale[0], object_snapshot.scale[1]);
+    object.set_visible(object_snapshot.visible);
+    object.set_flip_x(object_snapshot.flip_x);
+    object.set_flip_y(object_snapshot.flip_y);
+    if let (Some(current_frame), Some(mut atlas_config)) = (object_snapshot.current_frame, object.get_atlas_config()) {
+        atlas_config.current_frame = current_frame;
+        object.set_atlas_config(Some(atlas_config));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(index: usize) -> ObjectSnapshot {
+        ObjectSnapshot {
+            name: format!("object_{}", index),
+            position: [index as f32, -1.5, 0.25],
+            rotation: index as f32 * 0.1,
+            scale: [1.0, 2.0],
+            visible: index.is_multiple_of(2),
+            current_frame: if index.is_multiple_of(3) { Some(index) } else { None },
+            flip_x: index.is_multiple_of(4),
+            flip_y: index.is_multiple_of(5),
+        }
+    }
+
+    #[test]
+    fn round_trips_across_several_messages() {
+        let snapshot = Snapshot { tick: 42, objects: (0..100).map(object).collect() };
+        let messages = encode_snapshot(&snapshot, 256);
+        assert!(messages.len() > 1);
+
+        let mut decoded = Vec::new();
+        for message in &messages {
+            assert!(message.len() <= 256);
+            assert_eq!(message[0], REPLICATION_MESSAGE_TAG);
+            let part = decode_snapshot(&message[1..]).unwrap();
+            assert_eq!(part.tick, 42);
+            decoded.extend(part.objects);
+        }
+        assert_eq!(decoded, snapshot.objects);
+    }
+
+    #[test]
+    fn leaves_out_objects_too_large_for_a_message() {
+        let mut too_long = object(1);
+        too_long.name = "x".repeat(MAX_MESSAGE_SIZE);
+        let snapshot = Snapshot { tick: 7, objects: vec![object(0), too_long, object(2)] };
+
+        let messages = encode_snapshot(&snapshot, MAX_MESSAGE_SIZE);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(decode_snapshot(&messages[0][1..]).unwrap().objects, vec![object(0), object(2)]);
+    }
+
+    #[test]
+    fn rejects_truncated_messages() {
+        let snapshot = Snapshot { tick: 3, objects: vec![object(0), object(3)] };
+        let message = encode_snapshot(&snapshot, MAX_MESSAGE_SIZE).remove(0);
+        for length in 1..message.len() {
+            assert_eq!(decode_snapshot(&message[1..length]), None);
+        }
+    }
+
+    #[test]
+    fn rejects_trailing_bytes() {
+        let snapshot = Snapshot { tick: 3, objects: vec![object(0)] };
+        let mut message = encode_snapshot(&snapshot, MAX_MESSAGE_SIZE).remove(0);
+        message.push(0);
+        assert_eq!(decode_snapshot(&message[1..]), None);
+    }
+}
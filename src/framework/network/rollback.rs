@@ -0,0 +1,333 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use nalgebra::{Vector2, Vector3};
+
+use crate::framework::{error::{EngineError, EngineResult}, graphics::util::master_graphics_list::MasterGraphicsList};
+
+/// Implemented by games that use rollback netcode. step must be deterministic: the same state and inputs
+/// must always produce the same result on every machine, so no wall-clock time, unseeded randomness or HashMap iteration order.
+pub trait RollbackGame {
+    type Input: Clone + PartialEq + Default;
+    /// Game state that lives outside the registered graphics objects, e.g. health, score, RNG seed
+    type State: Clone;
+
+    fn save_state(&self) -> Self::State;
+
+    fn load_state(&mut self, state: &Self::State);
+
+    /// Advances the simulation by one fixed tick, with one input per player
+    fn step(&mut self, inputs: &[Self::Input], graphics_list: &MasterGraphicsList);
+}
+
+#[derive(Debug, Clone)]
+struct ObjectState {
+    position: Vector3<f32>,
+    rotation: f32,
+    scale: Vector2<f32>,
+    visible: bool,
+    active: bool,
+    flip_x: bool,
+    flip_y: bool,
+    current_frame: Option<usize>,
+    properties: HashMap<String, serde_json::Value>,
+}
+
+struct WorldState<S> {
+    objects: HashMap<String, ObjectState>,
+    game_state: S,
+}
+
+/// GGPO-style rollback session. Local input is delayed by a few ticks to hide latency, remote input that hasn't arrived
+/// is predicted by repeating the player's last input, and when a prediction turns out wrong the session restores the
+/// snapshot from that tick and resimulates forward with the corrected inputs.
+///
+/// Drive it from Game::fixed_update so ticks are evenly spaced, and send the (tick, input) pairs from add_local_input to the other players.
+pub struct RollbackSession<G: RollbackGame> {
+    num_players: usize,
+    local_player: usize,
+    input_delay: u32, // Ticks between local input being given and taking effect
+    max_rollback: u32, // How far ahead of the last confirmed remote input the session may run
+    current_tick: u32,
+    inputs: Vec<BTreeMap<u32, G::Input>>, // Per player, tick -> confirmed input
+    next_unconfirmed: Vec<u32>, // Per player, the first tick whose input hasn't arrived
+    predicted: Vec<BTreeMap<u32, G::Input>>, // Per player, tick -> input that was guessed when the tick was simulated
+    snapshots: VecDeque<(u32, WorldState<G::State>)>, // State at the start of each tick, oldest first
+    registered_objects: HashSet<String>,
+    rollback_to: Option<u32>, // Earliest tick that has to be resimulated
+}
+
+impl<G: RollbackGame> RollbackSession<G> {
+    /// Every player must use the same input_delay, since nobody has input for the first input_delay ticks.
+    /// Fails if local_player isn't one of the num_players.
+    pub fn new(num_players: usize, local_player: usize, input_delay: u32, max_rollback: u32) -> EngineResult<Self> {
+        if local_player >= num_players {
+            return Err(EngineError::InvalidRollbackPlayer { local_player, num_players });
+        }
+        let initial_inputs: BTreeMap<u32, G::Input> = (0..input_delay).map(|tick| (tick, G::Input::default())).collect();
+
+        Ok(RollbackSession {
+            num_players,
+            local_player,
+            input_delay,
+            max_rollback,
+            current_tick: 0,
+            inputs: vec![initial_inputs; num_players],
+            next_unconfirmed: vec![input_delay; num_players],
+            predicted: vec![BTreeMap::new(); num_players],
+            snapshots: VecDeque::new(),
+            registered_objects: HashSet::new(),
+            rollback_to: None,
+        })
+    }
+
+    /// Includes an object's transform, flags, animation frame and properties in snapshots
+    pub fn register_object(&mut self, name: &str) {
+        self.registered_objects.insert(name.to_owned());
+    }
+
+    pub fn unregister_object(&mut self, name: &str) {
+        self.registered_objects.remove(name);
+    }
+
+    pub fn get_current_tick(&self) -> u32 {
+        self.current_tick
+    }
+
+    /// Schedules the local player's input input_delay ticks ahead. Returns the tick it applies to, for sending to peers.
+    pub fn add_local_input(&mut self, input: G::Input) -> u32 {
+        let tick = self.current_tick + self.input_delay;
+        self.record_input(self.local_player, tick, input);
+        tick
+    }
+
+    /// Records a remote player's input for a tick. If that tick was already simulated with a different guess, a rollback is scheduled.
+    pub fn add_remote_input(&mut self, player: usize, tick: u32, input: G::Input) {
+        if player >= self.num_players || player == self.local_player {
+            return;
+        }
+
+        if tick < self.current_tick && self.predicted[player].get(&tick).is_some_and(|predicted| *predicted != input) {
+            self.request_rollback(tick);
+        }
+        self.predicted[player].remove(&tick);
+        self.record_input(player, tick, input);
+    }
+
+    fn record_input(&mut self, player: usize, tick: u32, input: G::Input) {
+        if tick < self.next_unconfirmed[player].saturating_sub(self.max_rollback + 1) {
+            return; // Too old to matter, and already pruned
+        }
+        self.inputs[player].insert(tick, input);
+        while self.inputs[player].contains_key(&self.next_unconfirmed[player]) {
+            self.next_unconfirmed[player] += 1;
+        }
+    }
+
+    /// Forces the ticks from the given one onwards to be resimulated on the next advance, if a snapshot for it is still kept
+    pub fn request_rollback(&mut self, tick: u32) {
+        self.rollback_to = Some(self.rollback_to.map_or(tick, |earliest| earliest.min(tick)));
+    }
+
+    /// The newest tick for which every player's input is known
+    pub fn get_confirmed_tick(&self) -> Option<u32> {
+        self.next_unconfirmed.iter().min().and_then(|tick| tick.checked_sub(1))
+    }
+
+    /// Whether running another tick would take the session further than max_rollback past the confirmed inputs
+    pub fn should_stall(&self) -> bool {
+        let first_unconfirmed = self.next_unconfirmed.iter().min().copied().unwrap_or(0);
+        self.current_tick >= first_unconfirmed + self.max_rollback
+    }
+
+    /// Resimulates from any pending rollback, then runs the current tick. Returns false without advancing
+    /// when the session has to wait for remote inputs (see should_stall).
+    /// Fails with RollbackSnapshotLost, without running the tick, if the rollback reached further back than the kept
+    /// snapshots. The peers no longer agree on the game state then, and the game has to resync or end the match.
+    pub fn advance(&mut self, game: &mut G, graphics_list: &MasterGraphicsList) -> EngineResult<bool> {
+        if let Some(rollback_tick) = self.rollback_to.take() {
+            self.resimulate_from(rollback_tick, game, graphics_list)?;
+        }
+
+        if self.should_stall() {
+            return Ok(false);
+        }
+
+        self.simulate_tick(game, graphics_list);
+        self.prune();
+        Ok(true)
+    }
+
+    fn resimulate_from(&mut self, tick: u32, game: &mut G, graphics_list: &MasterGraphicsList) -> EngineResult<()> {
+        if tick >= self.current_tick {
+            return Ok(()); // Not simulated yet, so it will use the corrected inputs anyway
+        }
+        let index = self.snapshots.iter().position(|(snapshot_tick, _)| *snapshot_tick == tick).ok_or(EngineError::RollbackSnapshotLost(tick))?;
+
+        self.restore(index, game, graphics_list);
+        let target_tick = self.current_tick;
+        self.current_tick = tick;
+        self.snapshots.truncate(index);
+        while self.current_tick < target_tick {
+            self.simulate_tick(game, graphics_list);
+        }
+        Ok(())
+    }
+
+    fn simulate_tick(&mut self, game: &mut G, graphics_list: &MasterGraphicsList) {
+        let snapshot = self.capture(game, graphics_list);
+        self.snapshots.push_back((self.current_tick, snapshot));
+
+        let tick = self.current_tick;
+        let inputs: Vec<G::Input> = (0..self.num_players)
+            .map(|player| match self.inputs[player].get(&tick) {
+                Some(input) => input.clone(),
+                None => {
+                    // Predict the player keeps doing what they last did
+                    let predicted = self.inputs[player].range(..tick).next_back().map(|(_, input)| input.clone()).unwrap_or_default();
+                    self.predicted[player].insert(tick, predicted.clone());
+                    predicted
+                }
+            })
+            .collect();
+
+        game.step(&inputs, graphics_list);
+        self.current_tick += 1;
+    }
+
+    fn capture(&self, game: &G, graphics_list: &MasterGraphicsList) -> WorldState<G::State> {
+        let objects = self.registered_objects
+            .iter()
+            .filter_map(|name| graphics_list.get_object(name).map(|object| (name, object)))
+            .map(|(name, object)| {
+                let object = object.read().unwrap();
+                (name.clone(), ObjectState {
+                    position: object.get_position(),
+                    rotation: object.get_rotation(),
+                    scale: object.get_scale(),
+                    visible: object.is_visible(),
+                    active: object.is_active(),
+                    flip_x: object.get_flip_x(),
+                    flip_y: object.get_flip_y(),
+                    current_frame: object.get_atlas_config().map(|atlas_config| atlas_config.current_frame),
+                    properties: object.get_properties().clone(),
+                })
+            })
+            .collect();
+
+        WorldState { objects, game_state: game.save_state() }
+    }
+
+    fn restore(&self, index: usize, game: &mut G, graphics_list: &MasterGraphicsList) {
+        let (_, world_state) = &self.snapshots[index];
+        game.load_state(&world_state.game_state);
+
+        for (name, state) in &world_state.objects {
+            let Some(object) = graphics_list.get_object(name) else {
+                continue;
+            };
+            let mut object = object.write().unwrap();
+            object.set_position(state.position);
+            object.set_rotation(state.rotation);
+            object.set_scale_xy(state.scale.x, state.scale.y);
+            object.set_visible(state.visible);
+            object.set_active(state.active);
+            object.set_flip_x(state.flip_x);
+            object.set_flip_y(state.flip_y);
+            object.set_properties(state.properties.clone());
+            if let (Some(current_frame), Some(mut atlas_config)) = (state.current_frame, object.get_atlas_config()) {
+                atlas_config.current_frame = current_frame;
+                object.set_atlas_config(Some(atlas_config));
+            }
+        }
+    }
+
+    /// Drops snapshots and inputs too old to ever be rolled back to
+    fn prune(&mut self) {
+        let oldest_kept = self.current_tick.saturating_sub(self.max_rollback + 1);
+        while self.snapshots.front().is_some_and(|(tick, _)| *tick < oldest_kept) {
+            self.snapshots.pop_front();
+        }
+        for player in 0..self.num_players {
+            // Keep one older input around as the basis for predictions
+            let keep_from = oldest_kept.saturating_sub(1);
+            self.inputs[player] = self.inputs[player].split_off(&keep_from);
+            self.predicted[player] = self.predicted[player].split_off(&oldest_kept);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Sums every input it is given, so a wrong prediction shows up in the total
+    #[derive(Default)]
+    struct SumGame {
+        sum: i64,
+    }
+
+    impl RollbackGame for SumGame {
+        type Input = i32;
+        type State = i64;
+
+        fn save_state(&self) -> i64 {
+            self.sum
+        }
+
+        fn load_state(&mut self, state: &i64) {
+            self.sum = *state;
+        }
+
+        fn step(&mut self, inputs: &[i32], _graphics_list: &MasterGraphicsList) {
+            self.sum += inputs.iter().map(|&input| input as i64).sum::<i64>();
+        }
+    }
+
+    #[test]
+    fn rejects_a_local_player_outside_the_session() {
+        assert!(matches!(
+            RollbackSession::<SumGame>::new(2, 2, 0, 8),
+            Err(EngineError::InvalidRollbackPlayer { local_player: 2, num_players: 2 })
+        ));
+        assert!(RollbackSession::<SumGame>::new(2, 1, 0, 8).is_ok());
+    }
+
+    #[test]
+    fn corrects_a_wrong_prediction() {
+        let graphics_list = MasterGraphicsList::new();
+        let mut game = SumGame::default();
+        let mut session = RollbackSession::<SumGame>::new(2, 0, 0, 8).unwrap();
+
+        session.add_local_input(1);
+        assert!(session.advance(&mut game, &graphics_list).unwrap());
+        assert_eq!(game.sum, 1); // The remote player was predicted to do nothing
+
+        session.add_remote_input(1, 0, 5);
+        session.add_local_input(1);
+        assert!(session.advance(&mut game, &graphics_list).unwrap());
+        assert_eq!(game.sum, 12); // Tick 0 redone as 1 + 5, then tick 1 predicts the remote player repeats 5
+    }
+
+    #[test]
+    fn reports_a_rollback_past_the_kept_snapshots() {
+        let graphics_list = MasterGraphicsList::new();
+        let mut game = SumGame::default();
+        let mut session = RollbackSession::<SumGame>::new(2, 0, 0, 2).unwrap();
+
+        for tick in 0..10 {
+            session.add_local_input(1);
+            session.add_remote_input(1, tick, 1);
+            assert!(session.advance(&mut game, &graphics_list).unwrap());
+        }
+
+        session.request_rollback(0);
+        assert!(matches!(session.advance(&mut game, &graphics_list), Err(EngineError::RollbackSnapshotLost(0))));
+        assert_eq!(session.get_current_tick(), 10);
+
+        // A rollback to a tick that hasn't run yet has nothing to redo
+        session.request_rollback(10);
+        session.add_local_input(1);
+        session.add_remote_input(1, 10, 1);
+        assert!(session.advance(&mut game, &graphics_list).unwrap());
+    }
+}
@@ -0,0 +1,47 @@
+use std::fmt;
+
+/// Identifies a connected peer within one transport. IDs are not reused while the transport is alive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PeerId(pub u32);
+
+impl fmt::Display for PeerId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "peer {}", self.0)
+    }
+}
+
+/// Delivery guarantees for a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    /// Fire and forget. Messages may be lost, and ones arriving after a newer message are dropped.
+    Unreliable,
+    /// Resent until acknowledged and delivered in the order they were sent
+    Reliable,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetworkEvent {
+    Connected(PeerId),
+    Disconnected(PeerId),
+    Message { peer: PeerId, channel: Channel, data: Vec<u8> },
+}
+
+/// The interface the replication layer talks to, so it can run over UDP or WebSockets alike
+pub trait Transport {
+    /// Queues data for a connected peer. Unknown peers are ignored.
+    fn send(&mut self, peer: PeerId, channel: Channel, data: &[u8]);
+
+    /// Sends data to every connected peer
+    fn broadcast(&mut self, channel: Channel, data: &[u8]) {
+        for peer in self.get_peers() {
+            self.send(peer, channel, data);
+        }
+    }
+
+    /// Reads everything that has arrived, does resends and keepalives, and reports what happened. Call once per frame.
+    fn poll(&mut self) -> Vec<NetworkEvent>;
+
+    fn disconnect(&mut self, peer: PeerId);
+
+    fn get_peers(&self) -> Vec<PeerId>;
+}
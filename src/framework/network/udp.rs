@@ -0,0 +1,367 @@
+use std::{collections::{BTreeMap, HashMap}, io::ErrorKind, net::{SocketAddr, ToSocketAddrs, UdpSocket}, time::{Duration, Instant}};
+
+use crate::framework::error::{EngineError, EngineResult};
+
+use super::transport::{Channel, NetworkEvent, PeerId, Transport};
+
+const MAX_PACKET_SIZE: usize = 1200; // Stays under common MTUs so packets aren't fragmented
+/// The largest message send accepts, what is left of a packet after the header and sequence number
+pub const MAX_MESSAGE_SIZE: usize = MAX_PACKET_SIZE - 7;
+
+const PACKET_CONNECT_REQUEST: u8 = 0;
+const PACKET_CONNECT_ACCEPT: u8 = 1;
+const PACKET_DISCONNECT: u8 = 2;
+const PACKET_KEEPALIVE: u8 = 3;
+const PACKET_UNRELIABLE: u8 = 4;
+const PACKET_RELIABLE: u8 = 5;
+const PACKET_ACK: u8 = 6;
+
+#[derive(Debug, Clone)]
+pub struct UdpConfig {
+    pub protocol_id: u32, // Packets with a different ID are ignored, so unrelated traffic and other games' versions don't connect
+    pub max_peers: usize,
+    pub resend_interval: Duration, // How long a reliable message or connect request waits for an ack before being sent again
+    pub keepalive_interval: Duration, // Sent when nothing else has gone to a peer for this long
+    pub timeout: Duration, // A peer that has sent nothing for this long is disconnected
+}
+
+impl Default for UdpConfig {
+    fn default() -> Self {
+        UdpConfig {
+            protocol_id: 0x5255_5354, // "RUST"
+            max_peers: 16,
+            resend_interval: Duration::from_millis(100),
+            keepalive_interval: Duration::from_millis(250),
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum PeerState {
+    Connecting, // We sent a connect request and are waiting for the accept
+    Connected,
+}
+
+struct Peer {
+    id: PeerId,
+    state: PeerState,
+    last_received: Instant,
+    last_sent: Instant,
+    connect_started: Instant,
+    unreliable_send_sequence: u16,
+    unreliable_receive_sequence: Option<u16>, // Newest unreliable sequence seen, older ones are dropped
+    reliable_send_id: u16,
+    reliable_unacked: BTreeMap<u16, (Vec<u8>, Instant)>, // Message ID -> (packet, last sent)
+    reliable_expected_id: u16, // Next reliable message ID to deliver
+    reliable_received: BTreeMap<u16, Vec<u8>>, // Arrived ahead of reliable_expected_id, held until the gap is filled
+}
+
+impl Peer {
+    fn new(id: PeerId, state: PeerState, now: Instant) -> Self {
+        Peer {
+            id,
+            state,
+            last_received: now,
+            last_sent: now,
+            connect_started: now,
+            unreliable_send_sequence: 0,
+            unreliable_receive_sequence: None,
+            reliable_send_id: 0,
+            reliable_unacked: BTreeMap::new(),
+            reliable_expected_id: 0,
+            reliable_received: BTreeMap::new(),
+        }
+    }
+}
+
+/// True if sequence a is newer than b, treating the u16 space as circular
+fn sequence_greater_than(a: u16, b: u16) -> bool {
+    a != b && a.wrapping_sub(b) < u16::MAX / 2
+}
+
+/// Non-blocking UDP transport with a connection handshake, keepalives and timeouts,
+/// plus unreliable (sequenced) and reliable (acknowledged, ordered) channels.
+/// The same type is used by servers (bind to a known port, accept connections) and clients (bind to port 0, connect).
+pub struct UdpTransport {
+    socket: UdpSocket,
+    config: UdpConfig,
+    peers: HashMap<SocketAddr, Peer>,
+    next_peer_id: u32,
+    accept_connections: bool,
+    events: Vec<NetworkEvent>,
+}
+
+impl UdpTransport {
+    pub fn bind(address: &str, config: UdpConfig) -> EngineResult<Self> {
+        let socket = UdpSocket::bind(address).map_err(|e| EngineError::io(address, e))?;
+        socket.set_nonblocking(true).map_err(|e| EngineError::io(address, e))?;
+
+        Ok(UdpTransport {
+            socket,
+            config,
+            peers: HashMap::new(),
+            next_peer_id: 0,
+            accept_connections: false,
+            events: Vec::new(),
+        })
+    }
+
+    /// Whether connect requests from unknown addresses are accepted, i.e. whether this acts as a server
+    pub fn set_accept_connections(&mut self, accept_connections: bool) {
+        self.accept_connections = accept_connections;
+    }
+
+    pub fn get_local_address(&self) -> EngineResult<SocketAddr> {
+        self.socket.local_addr().map_err(|e| EngineError::io("local address", e))
+    }
+
+    /// Starts connecting to a server. Returns the peer's ID now; NetworkEvent::Connected follows once it accepts,
+    /// or Disconnected if it doesn't answer within the timeout.
+    pub fn connect(&mut self, address: &str) -> EngineResult<PeerId> {
+        let address = address
+            .to_socket_addrs()
+            .map_err(|e| EngineError::io(address, e))?
+            .next()
+            .ok_or_else(|| EngineError::Network(format!("'{}' did not resolve to an address", address)))?;
+
+        if let Some(peer) = self.peers.get(&address) {
+            return Ok(peer.id);
+        }
+
+        let id = self.allocate_peer_id();
+        let mut peer = Peer::new(id, PeerState::Connecting, Instant::now());
+        let packet = self.header(PACKET_CONNECT_REQUEST);
+        Self::send_raw(&self.socket, &mut peer, address, &packet);
+        self.peers.insert(address, peer);
+        Ok(id)
+    }
+
+    pub fn get_peer_address(&self, peer_id: PeerId) -> Option<SocketAddr> {
+        self.peers.iter().find(|(_, peer)| peer.id == peer_id).map(|(address, _)| *address)
+    }
+
+    pub fn is_connected(&self, peer_id: PeerId) -> bool {
+        self.peers.values().any(|peer| peer.id == peer_id && peer.state == PeerState::Connected)
+    }
+
+    fn allocate_peer_id(&mut self) -> PeerId {
+        let id = PeerId(self.next_peer_id);
+        self.next_peer_id += 1;
+        id
+    }
+
+    fn header(&self, packet_type: u8) -> Vec<u8> {
+        let mut packet = Vec::with_capacity(MAX_PACKET_SIZE);
+        packet.extend_from_slice(&self.config.protocol_id.to_le_bytes());
+        packet.push(packet_type);
+        packet
+    }
+
+    fn send_raw(socket: &UdpSocket, peer: &mut Peer, address: SocketAddr, packet: &[u8]) {
+        if let Err(e) = socket.send_to(packet, address) {
+            if e.kind() != ErrorKind::WouldBlock {
+                log::warn!("Failed to send to {}: {}", address, e);
+            }
+        }
+        peer.last_sent = Instant::now();
+    }
+
+    fn receive_packets(&mut self) {
+        let mut buffer = [0u8; MAX_PACKET_SIZE];
+        loop {
+            let (length, address) = match self.socket.recv_from(&mut buffer) {
+                Ok(received) => received,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::ConnectionReset => continue, // Windows reports ICMP unreachable this way
+                Err(e) => {
+                    log::warn!("Failed to receive: {}", e);
+                    break;
+                }
+            };
+
+            let packet = &buffer[..length];
+            if packet.len() < 5 || packet[0..4] != self.config.protocol_id.to_le_bytes() {
+                continue;
+            }
+            self.handle_packet(address, packet[4], &packet[5..]);
+        }
+    }
+
+    fn handle_packet(&mut self, address: SocketAddr, packet_type: u8, body: &[u8]) {
+        let now = Instant::now();
+
+        if packet_type == PACKET_CONNECT_REQUEST && !self.peers.contains_key(&address) {
+            let connected = self.peers.values().filter(|peer| peer.state == PeerState::Connected).count();
+            if !self.accept_connections || connected >= self.config.max_peers {
+                let packet = self.header(PACKET_DISCONNECT);
+                let _ = self.socket.send_to(&packet, address);
+                return;
+            }
+            let id = self.allocate_peer_id();
+            self.peers.insert(address, Peer::new(id, PeerState::Connected, now));
+            self.events.push(NetworkEvent::Connected(id));
+        }
+
+        let accept_packet = self.header(PACKET_CONNECT_ACCEPT);
+        let ack_header = self.header(PACKET_ACK);
+        let Some(peer) = self.peers.get_mut(&address) else {
+            return; // Not from a peer we know
+        };
+        peer.last_received = now;
+
+        match packet_type {
+            PACKET_CONNECT_REQUEST => {
+                // Also answers repeats, in case our earlier accept was lost
+                let _ = self.socket.send_to(&accept_packet, address);
+                peer.last_sent = now;
+            }
+            PACKET_CONNECT_ACCEPT if peer.state == PeerState::Connecting => {
+                peer.state = PeerState::Connected;
+                self.events.push(NetworkEvent::Connected(peer.id));
+            }
+            PACKET_DISCONNECT => {
+                let id = peer.id;
+                self.peers.remove(&address);
+                self.events.push(NetworkEvent::Disconnected(id));
+            }
+            PACKET_UNRELIABLE if body.len() >= 2 && peer.state == PeerState::Connected => {
+                let sequence = u16::from_le_bytes([body[0], body[1]]);
+                if peer.unreliable_receive_sequence.is_none_or(|latest| sequence_greater_than(sequence, latest)) {
+                    peer.unreliable_receive_sequence = Some(sequence);
+                    self.events.push(NetworkEvent::Message { peer: peer.id, channel: Channel::Unreliable, data: body[2..].to_vec() });
+                }
+            }
+            PACKET_RELIABLE if body.len() >= 2 && peer.state == PeerState::Connected => {
+                let message_id = u16::from_le_bytes([body[0], body[1]]);
+
+                // Always ack, duplicates mean our previous ack was lost
+                let mut ack = ack_header;
+                ack.extend_from_slice(&message_id.to_le_bytes());
+                let _ = self.socket.send_to(&ack, address);
+                peer.last_sent = now;
+
+                if message_id == peer.reliable_expected_id || sequence_greater_than(message_id, peer.reliable_expected_id) {
+                    peer.reliable_received.insert(message_id, body[2..].to_vec());
+                }
+                while let Some(data) = peer.reliable_received.remove(&peer.reliable_expected_id) {
+                    self.events.push(NetworkEvent::Message { peer: peer.id, channel: Channel::Reliable, data });
+                    peer.reliable_expected_id = peer.reliable_expected_id.wrapping_add(1);
+                }
+            }
+            PACKET_ACK if body.len() >= 2 => {
+                peer.reliable_unacked.remove(&u16::from_le_bytes([body[0], body[1]]));
+            }
+            _ => {} // Keepalives only refresh last_received
+        }
+    }
+
+    fn service_peers(&mut self) {
+        let now = Instant::now();
+        let mut timed_out = Vec::new();
+        let connect_request = self.header(PACKET_CONNECT_REQUEST);
+        let keepalive = self.header(PACKET_KEEPALIVE);
+
+        for (address, peer) in self.peers.iter_mut() {
+            let waited_since = if peer.state == PeerState::Connecting { peer.connect_started.max(peer.last_received) } else { peer.last_received };
+            if now.duration_since(waited_since) > self.config.timeout {
+                timed_out.push(*address);
+                continue;
+            }
+
+            let mut outgoing: Vec<Vec<u8>> = Vec::new();
+            match peer.state {
+                PeerState::Connecting => {
+                    if now.duration_since(peer.last_sent) >= self.config.resend_interval {
+                        outgoing.push(connect_request.clone());
+                    }
+                }
+                PeerState::Connected => {
+                    for (packet, last_sent) in peer.reliable_unacked.values_mut() {
+                        if now.duration_since(*last_sent) >= self.config.resend_interval {
+                            *last_sent = now;
+                            outgoing.push(packet.clone());
+                        }
+                    }
+                    if outgoing.is_empty() && now.duration_since(peer.last_sent) >= self.config.keepalive_interval {
+                        outgoing.push(keepalive.clone());
+                    }
+                }
+            }
+
+            for packet in outgoing {
+                Self::send_raw(&self.socket, peer, *address, &packet);
+            }
+        }
+
+        for address in timed_out {
+            if let Some(peer) = self.peers.remove(&address) {
+                log::info!("{} at {} timed out", peer.id, address);
+                self.events.push(NetworkEvent::Disconnected(peer.id));
+            }
+        }
+    }
+}
+
+impl Transport for UdpTransport {
+    fn send(&mut self, peer_id: PeerId, channel: Channel, data: &[u8]) {
+        if data.len() > MAX_MESSAGE_SIZE {
+            log::warn!("Dropping a {} byte message to {}, larger than the {} byte message limit", data.len(), peer_id, MAX_MESSAGE_SIZE);
+            return;
+        }
+
+        let Some(address) = self.get_peer_address(peer_id) else {
+            return;
+        };
+        let mut packet = self.header(match channel {
+            Channel::Unreliable => PACKET_UNRELIABLE,
+            Channel::Reliable => PACKET_RELIABLE,
+        });
+
+        let Some(peer) = self.peers.get_mut(&address) else {
+            return;
+        };
+        if peer.state == PeerState::Connected {
+            match channel {
+                Channel::Unreliable => {
+                    packet.extend_from_slice(&peer.unreliable_send_sequence.to_le_bytes());
+                    peer.unreliable_send_sequence = peer.unreliable_send_sequence.wrapping_add(1);
+                }
+                Channel::Reliable => {
+                    packet.extend_from_slice(&peer.reliable_send_id.to_le_bytes());
+                    peer.reliable_send_id = peer.reliable_send_id.wrapping_add(1);
+                }
+            }
+            packet.extend_from_slice(data);
+            if channel == Channel::Reliable {
+                let message_id = peer.reliable_send_id.wrapping_sub(1);
+                peer.reliable_unacked.insert(message_id, (packet.clone(), Instant::now()));
+            }
+            Self::send_raw(&self.socket, peer, address, &packet);
+        }
+    }
+
+    fn poll(&mut self) -> Vec<NetworkEvent> {
+        self.receive_packets();
+        self.service_peers();
+        std::mem::take(&mut self.events)
+    }
+
+    fn disconnect(&mut self, peer_id: PeerId) {
+        if let Some(address) = self.get_peer_address(peer_id) {
+            let packet = self.header(PACKET_DISCONNECT);
+            // Unacknowledged, so send a few in case some are lost
+            for _ in 0..3 {
+                let _ = self.socket.send_to(&packet, address);
+            }
+            self.peers.remove(&address);
+            self.events.push(NetworkEvent::Disconnected(peer_id));
+        }
+    }
+
+    fn get_peers(&self) -> Vec<PeerId> {
+        let mut peers: Vec<PeerId> = self.peers.values().filter(|peer| peer.state == PeerState::Connected).map(|peer| peer.id).collect();
+        peers.sort();
+        peers
+    }
+}
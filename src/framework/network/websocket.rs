@@ -0,0 +1,183 @@
+use std::{io::ErrorKind, net::{TcpListener, TcpStream}, time::Duration};
+
+use tungstenite::{client::IntoClientRequest, Message, WebSocket};
+
+use crate::framework::error::{EngineError, EngineResult};
+
+use super::transport::{Channel, NetworkEvent, PeerId, Transport};
+
+// Upper bound on a blocking handshake, so one slow client can't stall the frame for long
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// WebSocket transport (feature "websocket") implementing the same Transport interface as UdpTransport,
+/// so browser clients and relay servers can take part in the same replication layer.
+/// TCP already delivers everything in order, so both channels are reliable here; the channel is carried along
+/// in each message so the receiving side sees what the sender asked for.
+pub struct WebSocketTransport {
+    listener: Option<TcpListener>,
+    peers: Vec<(PeerId, WebSocket<TcpStream>)>,
+    next_peer_id: u32,
+    events: Vec<NetworkEvent>,
+}
+
+impl WebSocketTransport {
+    /// Listens for incoming WebSocket connections, e.g. on "0.0.0.0:9001"
+    pub fn listen(address: &str) -> EngineResult<Self> {
+        let listener = TcpListener::bind(address).map_err(|e| EngineError::io(address, e))?;
+        listener.set_nonblocking(true).map_err(|e| EngineError::io(address, e))?;
+
+        Ok(WebSocketTransport {
+            listener: Some(listener),
+            peers: Vec::new(),
+            next_peer_id: 0,
+            events: Vec::new(),
+        })
+    }
+
+    /// Connects to a WebSocket server, e.g. "ws://127.0.0.1:9001". Blocks until the handshake completes.
+    /// Only plain ws:// is supported, TLS isn't compiled in.
+    pub fn connect(url: &str) -> EngineResult<(Self, PeerId)> {
+        let request = url.into_client_request().map_err(|e| EngineError::Network(format!("Invalid WebSocket URL '{}': {}", url, e)))?;
+        if request.uri().scheme_str() != Some("ws") {
+            return Err(EngineError::Network(format!("WebSocket URL '{}' must use ws://, secure wss:// connections aren't supported", url)));
+        }
+        let host = request.uri().host().ok_or_else(|| EngineError::Network(format!("WebSocket URL '{}' has no host", url)))?;
+        let address = format!("{}:{}", host, request.uri().port_u16().unwrap_or(80));
+
+        let stream = TcpStream::connect(&address).map_err(|e| EngineError::io(&address, e))?;
+        stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT)).map_err(|e| EngineError::io(&address, e))?;
+        let (socket, _) = tungstenite::client(request, stream).map_err(|e| EngineError::Network(format!("WebSocket handshake with '{}' failed: {}", url, e)))?;
+
+        let mut transport = WebSocketTransport {
+            listener: None,
+            peers: Vec::new(),
+            next_peer_id: 0,
+            events: Vec::new(),
+        };
+        let peer = transport.add_peer(socket)?;
+        Ok((transport, peer))
+    }
+
+    fn add_peer(&mut self, socket: WebSocket<TcpStream>) -> EngineResult<PeerId> {
+        socket.get_ref().set_nonblocking(true).map_err(|e| EngineError::io("websocket", e))?;
+        socket.get_ref().set_nodelay(true).map_err(|e| EngineError::io("websocket", e))?;
+
+        let id = PeerId(self.next_peer_id);
+        self.next_peer_id += 1;
+        self.peers.push((id, socket));
+        self.events.push(NetworkEvent::Connected(id));
+        Ok(id)
+    }
+
+    fn accept_connections(&mut self) {
+        let Some(listener) = &self.listener else {
+            return;
+        };
+
+        let mut accepted = Vec::new();
+        loop {
+            match listener.accept() {
+                Ok((stream, address)) => {
+                    let handshake = stream
+                        .set_nonblocking(false)
+                        .and_then(|_| stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT)))
+                        .map_err(|e| e.to_string())
+                        .and_then(|_| tungstenite::accept(stream).map_err(|e| e.to_string()));
+                    match handshake {
+                        Ok(socket) => accepted.push(socket),
+                        Err(e) => log::warn!("WebSocket handshake with {} failed: {}", address, e),
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    log::warn!("Failed to accept a WebSocket connection: {}", e);
+                    break;
+                }
+            }
+        }
+
+        for socket in accepted {
+            if let Err(e) = self.add_peer(socket) {
+                log::warn!("Failed to set up WebSocket peer: {}", e);
+            }
+        }
+    }
+
+    fn is_would_block(error: &tungstenite::Error) -> bool {
+        matches!(error, tungstenite::Error::Io(e) if e.kind() == ErrorKind::WouldBlock)
+    }
+}
+
+impl Transport for WebSocketTransport {
+    fn send(&mut self, peer: PeerId, channel: Channel, data: &[u8]) {
+        let Some((_, socket)) = self.peers.iter_mut().find(|(id, _)| *id == peer) else {
+            return;
+        };
+
+        let mut message = Vec::with_capacity(data.len() + 1);
+        message.push(match channel {
+            Channel::Unreliable => 0,
+            Channel::Reliable => 1,
+        });
+        message.extend_from_slice(data);
+
+        // A would-block here means the frame is queued; poll keeps flushing it
+        if let Err(e) = socket.write(Message::binary(message)) {
+            if !Self::is_would_block(&e) {
+                log::warn!("Failed to send to {}: {}", peer, e);
+            }
+        }
+    }
+
+    fn poll(&mut self) -> Vec<NetworkEvent> {
+        self.accept_connections();
+
+        let mut closed = Vec::new();
+        for (peer, socket) in self.peers.iter_mut() {
+            loop {
+                match socket.read() {
+                    Ok(Message::Binary(data)) if !data.is_empty() => {
+                        let channel = if data[0] == 0 { Channel::Unreliable } else { Channel::Reliable };
+                        self.events.push(NetworkEvent::Message { peer: *peer, channel, data: data[1..].to_vec() });
+                    }
+                    Ok(Message::Close(_)) => {
+                        closed.push(*peer);
+                        break;
+                    }
+                    Ok(_) => {} // Pings are answered by tungstenite, text frames aren't part of the protocol
+                    Err(e) if Self::is_would_block(&e) => break,
+                    Err(_) => {
+                        closed.push(*peer);
+                        break;
+                    }
+                }
+            }
+
+            if let Err(e) = socket.flush() {
+                if !Self::is_would_block(&e) && !closed.contains(peer) {
+                    closed.push(*peer);
+                }
+            }
+        }
+
+        for peer in closed {
+            self.peers.retain(|(id, _)| *id != peer);
+            self.events.push(NetworkEvent::Disconnected(peer));
+        }
+
+        std::mem::take(&mut self.events)
+    }
+
+    fn disconnect(&mut self, peer: PeerId) {
+        if let Some(index) = self.peers.iter().position(|(id, _)| *id == peer) {
+            let (_, mut socket) = self.peers.remove(index);
+            let _ = socket.close(None);
+            let _ = socket.flush();
+            self.events.push(NetworkEvent::Disconnected(peer));
+        }
+    }
+
+    fn get_peers(&self) -> Vec<PeerId> {
+        self.peers.iter().map(|(id, _)| *id).collect()
+    }
+}
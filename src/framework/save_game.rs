@@ -0,0 +1,231 @@
+use std::{collections::{HashMap, HashSet}, fs, path::PathBuf, thread::JoinHandle};
+use nalgebra::Vector3;
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+
+use super::{error::{EngineError, EngineResult}, graphics::util::master_graphics_list::MasterGraphicsList};
+
+#[derive(Serialize, Debug, Clone, Deserialize)]
+pub struct SavedObject {
+    pub name: String,
+    pub position: [f32; 3],
+    pub rotation: f32,
+    #[serde(deserialize_with = "deserialize_scale")]
+    pub scale: [f32; 2], // Version 1 saves stored a single uniform scale
+    pub visible: bool,
+    pub active: bool,
+    pub current_frame: Option<usize>,
+    pub properties: HashMap<String, serde_json::Value>,
+}
+
+fn deserialize_scale<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[f32; 2], D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Scale {
+        Uniform(f32),
+        PerAxis([f32; 2]),
+    }
+
+    Ok(match Scale::deserialize(deserializer)? {
+        Scale::Uniform(scale) => [scale, scale],
+        Scale::PerAxis(scale) => scale,
+    })
+}
+
+#[derive(Serialize, Debug, Clone, Deserialize)]
+pub struct SaveData {
+    pub version: u32,
+    pub objects: Vec<SavedObject>,
+    pub game_data: HashMap<String, serde_json::Value>, // Custom game state stored with set_data
+}
+
+/// Saves registered objects and custom game data into JSON save slots
+pub struct SaveGame {
+    directory: PathBuf,
+    registered_objects: HashSet<String>,
+    game_data: HashMap<String, serde_json::Value>,
+    pending_write: Option<JoinHandle<EngineResult<()>>>,
+}
+
+impl SaveGame {
+    pub const SAVE_VERSION: u32 = 2;
+
+    /// Stores saves in the platform's user data directory, under "{game_name}/saves"
+    pub fn new(game_name: &str) -> Self {
+        let base = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
+        Self::with_directory(base.join(game_name).join("saves"))
+    }
+
+    /// Stores saves in a specific directory
+    pub fn with_directory(directory: PathBuf) -> Self {
+        SaveGame {
+            directory,
+            registered_objects: HashSet::new(),
+            game_data: HashMap::new(),
+            pending_write: None,
+        }
+    }
+
+    /// Includes the named object's transform, flags, animation frame and properties in saves
+    pub fn register_object(&mut self, name: &str) {
+        self.registered_objects.insert(name.to_owned());
+    }
+
+    pub fn unregister_object(&mut self, name: &str) {
+        self.registered_objects.remove(name);
+    }
+
+    /// Stores a piece of custom game state to be written with the next save
+    pub fn set_data<T: Serialize>(&mut self, key: &str, value: &T) -> EngineResult<()> {
+        let value = serde_json::to_value(value).map_err(EngineError::Serialize)?;
+        self.game_data.insert(key.to_owned(), value);
+        Ok(())
+    }
+
+    /// Reads back custom game state, e.g. after load
+    pub fn get_data<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        self.game_data.get(key).and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
+
+    /// Snapshots the registered objects and game data
+    pub fn capture(&self, graphics_list: &MasterGraphicsList) -> SaveData {
+        let mut objects = Vec::new();
+        for name in &self.registered_objects {
+            if let Some(object) = graphics_list.get_object(name) {
+                let object = object.read().unwrap();
+                let position = object.get_position();
+                let scale = object.get_scale();
+                objects.push(SavedObject {
+                    name: name.clone(),
+                    position: [position.x, position.y, position.z],
+                    rotation: object.get_rotation(),
+                    scale: [scale.x, scale.y],
+                    visible: object.is_visible(),
+                    active: object.is_active(),
+                    current_frame: object.get_atlas_config().map(|atlas_config| atlas_config.current_frame),
+                    properties: object.get_properties().clone(),
+                });
+            }
+        }
+
+        SaveData {
+            version: Self::SAVE_VERSION,
+            objects,
+            game_data: self.game_data.clone(),
+        }
+    }
+
+    /// Writes a save slot on a background thread so the frame isn't held up by disk IO
+    pub fn save(&mut self, slot: &str, graphics_list: &MasterGraphicsList) -> EngineResult<()> {
+        // Only one write in flight, so slots are never written out of order
+        self.wait_for_pending_write()?;
+
+        let path = self.slot_path(slot)?;
+        let contents = serde_json::to_string_pretty(&self.capture(graphics_list)).map_err(EngineError::Serialize)?;
+        let directory = self.directory.clone();
+        self.pending_write = Some(std::thread::spawn(move || Self::write_slot(directory, path, contents)));
+        Ok(())
+    }
+
+    /// Writes a save slot, returning once it is on disk
+    pub fn save_blocking(&mut self, slot: &str, graphics_list: &MasterGraphicsList) -> EngineResult<()> {
+        self.save(slot, graphics_list)?;
+        self.wait_for_pending_write()
+    }
+
+    fn write_slot(directory: PathBuf, path: PathBuf, contents: String) -> EngineResult<()> {
+        fs::create_dir_all(&directory).map_err(|e| EngineError::io(directory.display().to_string(), e))?;
+
+        // Write to a temporary file first so a crash mid-write can't corrupt the existing save
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, contents).map_err(|e| EngineError::io(temp_path.display().to_string(), e))?;
+        fs::rename(&temp_path, &path).map_err(|e| EngineError::io(path.display().to_string(), e))
+    }
+
+    /// Blocks until the last background save has finished, returning its result
+    pub fn wait_for_pending_write(&mut self) -> EngineResult<()> {
+        match self.pending_write.take() {
+            Some(handle) => handle.join().map_err(|_| EngineError::SaveThreadPanicked)?,
+            None => Ok(()),
+        }
+    }
+
+    /// Reads a save slot, restores the game data and applies saved state to objects that exist in the list
+    pub fn load(&mut self, slot: &str, graphics_list: &MasterGraphicsList) -> EngineResult<SaveData> {
+        self.wait_for_pending_write()?;
+
+        let path = self.slot_path(slot)?.display().to_string();
+        let contents = fs::read_to_string(&path).map_err(|e| EngineError::io(path.clone(), e))?;
+        let save_data: SaveData = serde_json::from_str(&contents).map_err(|e| EngineError::parse(path, e))?;
+        if save_data.version > Self::SAVE_VERSION {
+            return Err(EngineError::SaveVersion { slot: slot.to_owned(), version: save_data.version });
+        }
+
+        for saved in &save_data.objects {
+            let Some(object) = graphics_list.get_object(&saved.name) else {
+                log::warn!("Saved object '{}' is not in the graphics list, skipping", saved.name);
+                continue;
+            };
+            let mut object = object.write().unwrap();
+            let [x, y, z] = saved.position;
+            object.set_position(Vector3::new(x, y, z));
+            object.set_rotation(saved.rotation);
+            object.set_scale_xy(saved.scale[0], saved.scale[1]);
+            object.set_visible(saved.visible);
+            object.set_active(saved.active);
+            object.set_properties(saved.properties.clone());
+            if let (Some(current_frame), Some(mut atlas_config)) = (saved.current_frame, object.get_atlas_config()) {
+                atlas_config.current_frame = current_frame;
+                object.set_atlas_config(Some(atlas_config));
+            }
+        }
+
+        self.game_data = save_data.game_data.clone();
+        Ok(save_data)
+    }
+
+    /// Names of the existing save slots
+    pub fn list_slots(&self) -> Vec<String> {
+        let Ok(entries) = fs::read_dir(&self.directory) else {
+            return Vec::new();
+        };
+
+        entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+            .filter_map(|path| path.file_stem().and_then(|stem| stem.to_str()).map(|stem| stem.to_owned()))
+            .collect()
+    }
+
+    pub fn delete_slot(&self, slot: &str) -> EngineResult<()> {
+        let path = self.slot_path(slot)?;
+        fs::remove_file(&path).map_err(|e| EngineError::io(path.display().to_string(), e))
+    }
+
+    // Slot names become file names, so one that could point outside the save directory is refused
+    fn slot_path(&self, slot: &str) -> EngineResult<PathBuf> {
+        if slot.is_empty() || slot.contains(['/', '\\', ':']) || slot.contains("..") {
+            return Err(EngineError::InvalidSaveSlot(slot.to_owned()));
+        }
+        Ok(self.directory.join(format!("{}.json", slot)))
+    }
+
+    pub fn get_directory(&self) -> &PathBuf {
+        &self.directory
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slot_names_stay_inside_the_save_directory() {
+        let save_game = SaveGame::with_directory(PathBuf::from("saves"));
+        assert_eq!(save_game.slot_path("slot_1").unwrap(), PathBuf::from("saves").join("slot_1.json"));
+
+        for slot in ["", "../settings", "..", "nested/slot", "nested\\slot", "/etc/passwd", "C:evil"] {
+            assert!(matches!(save_game.slot_path(slot), Err(EngineError::InvalidSaveSlot(_))), "{:?} was accepted", slot);
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use std::{collections::HashMap, sync::{Arc, RwLock}};
+use nalgebra::Vector3;
+use rhai::{CallFnOptions, Engine, Scope, AST};
+
+use crate::framework::events::movement::{move_object, rotate_object};
+use super::error::{EngineError, EngineResult};
+use super::graphics::{camera::Camera, internal_object::graphics_object::Generic2DGraphicsObject, util::master_graphics_list::MasterGraphicsList};
+
+struct AttachedScript {
+    ast: AST,
+    scope: Scope<'static>, // Keeps script-level variables alive between updates
+}
+
+/// Runs rhai scripts attached to objects. Each script may define `fn update(name, delta_time)`,
+/// which is called every update with the name of the object it is attached to.
+pub struct ScriptEngine {
+    engine: Engine,
+    scripts: HashMap<String, AttachedScript>, // Keyed by object name
+}
+
+impl ScriptEngine {
+    pub fn new(master_graphics_list: Arc<RwLock<MasterGraphicsList>>, camera: Arc<RwLock<Camera>>) -> Self {
+        let mut engine = Engine::new();
+        Self::register_object_api(&mut engine, master_graphics_list);
+        Self::register_camera_api(&mut engine, camera);
+
+        ScriptEngine {
+            engine,
+            scripts: HashMap::new(),
+        }
+    }
+
+    fn register_object_api(engine: &mut Engine, master_graphics_list: Arc<RwLock<MasterGraphicsList>>) {
+        // Runs the closure against a named object, doing nothing if it doesn't exist
+        fn with_object<T: Default>(list: &Arc<RwLock<MasterGraphicsList>>, name: &str, f: impl FnOnce(&mut Generic2DGraphicsObject) -> T) -> T {
+            match list.read().unwrap().get_object(name) {
+                Some(object) => f(&mut object.write().unwrap()),
+                None => T::default(),
+            }
+        }
+
+        let list = master_graphics_list.clone();
+        engine.register_fn("object_exists", move |name: &str| list.read().unwrap().get_object(name).is_some());
+
+        let list = master_graphics_list.clone();
+        engine.register_fn("get_x", move |name: &str| with_object(&list, name, |object| object.get_position().x as f64));
+
+        let list = master_graphics_list.clone();
+        engine.register_fn("get_y", move |name: &str| with_object(&list, name, |object| object.get_position().y as f64));
+
+        let list = master_graphics_list.clone();
+        engine.register_fn("set_position", move |name: &str, x: f64, y: f64| {
+            with_object(&list, name, |object| {
+                let z = object.get_position().z;
+                object.set_position(Vector3::new(x as f32, y as f32, z));
+            })
+        });
+
+        let list = master_graphics_list.clone();
+        engine.register_fn("move_object", move |name: &str, dx: f64, dy: f64, delta_time: f64| {
+            with_object(&list, name, |object| move_object(object, Vector3::new(dx as f32, dy as f32, 0.0), delta_time as f32))
+        });
+
+        let list = master_graphics_list.clone();
+        engine.register_fn("rotate_object", move |name: &str, angle: f64| {
+            with_object(&list, name, |object| rotate_object(object, angle as f32))
+        });
+
+        let list = master_graphics_list.clone();
+        engine.register_fn("get_rotation", move |name: &str| with_object(&list, name, |object| object.get_rotation() as f64));
+
+        let list = master_graphics_list.clone();
+        engine.register_fn("set_rotation", move |name: &str, rotation: f64| {
+            with_object(&list, name, |object| object.set_rotation(rotation as f32))
+        });
+
+        let list = master_graphics_list.clone();
+        engine.register_fn("set_scale", move |name: &str, scale: f64| {
+            with_object(&list, name, |object| object.set_scale(scale as f32))
+        });
+
+        let list = master_graphics_list.clone();
+        engine.register_fn("set_scale_xy", move |name: &str, scale_x: f64, scale_y: f64| {
+            with_object(&list, name, |object| object.set_scale_xy(scale_x as f32, scale_y as f32))
+        });
+
+        let list = master_graphics_list.clone();
+        engine.register_fn("set_visible", move |name: &str, visible: bool| {
+            with_object(&list, name, |object| object.set_visible(visible))
+        });
+
+        // Queued, so a script can remove its own object while scripts are being run
+        let list = master_graphics_list.clone();
+        engine.register_fn("remove_object", move |name: &str| list.read().unwrap().queue_remove(name));
+    }
+
+    fn register_camera_api(engine: &mut Engine, camera: Arc<RwLock<Camera>>) {
+        let camera_handle = camera.clone();
+        engine.register_fn("camera_track", move |name: &str| {
+            camera_handle.write().unwrap().set_tracking_target(Some(name.to_owned()));
+        });
+
+        let camera_handle = camera.clone();
+        engine.register_fn("camera_stop_tracking", move || camera_handle.write().unwrap().set_tracking_target(None));
+
+        let camera_handle = camera.clone();
+        engine.register_fn("camera_set_zoom", move |zoom: f64| camera_handle.write().unwrap().set_zoom(zoom as f32));
+
+        let camera_handle = camera;
+        engine.register_fn("camera_get_zoom", move || camera_handle.read().unwrap().get_zoom() as f64);
+    }
+
+    /// Compiles a script and attaches it to an object, replacing any script already attached.
+    /// Top-level statements run once here and never again, so they can set up script variables that update() then reads and changes.
+    pub fn attach_script(&mut self, object_name: &str, source: &str) -> EngineResult<()> {
+        let script_error = |message: String| EngineError::Script { object: object_name.to_owned(), message };
+        let ast = self.engine.compile(source).map_err(|e| script_error(e.to_string()))?;
+        let mut scope = Scope::new();
+        self.engine.run_ast_with_scope(&mut scope, &ast).map_err(|e| script_error(e.to_string()))?;
+
+        self.scripts.insert(object_name.to_owned(), AttachedScript { ast, scope });
+        Ok(())
+    }
+
+    /// Loads a script file and attaches it to an object
+    pub fn attach_script_file(&mut self, object_name: &str, path: &str) -> EngineResult<()> {
+        let source = std::fs::read_to_string(path).map_err(|e| EngineError::io(path, e))?;
+        self.attach_script(object_name, &source)
+    }
+
+    pub fn detach_script(&mut self, object_name: &str) {
+        self.scripts.remove(object_name);
+    }
+
+    /// Calls update(name, delta_time) in every attached script that defines it. Errors are printed and don't stop other scripts.
+    pub fn update(&mut self, delta_time: f32) {
+        for (object_name, script) in self.scripts.iter_mut() {
+            let defines_update = script.ast.iter_functions().any(|function| function.name == "update" && function.params.len() == 2);
+            if !defines_update {
+                continue;
+            }
+
+            // Skip the top-level statements, attach_script already ran them
+            let options = CallFnOptions::new().eval_ast(false).rewind_scope(false);
+            let result = self.engine.call_fn_with_options::<()>(options, &mut script.scope, &script.ast, "update", (object_name.clone(), delta_time as f64));
+            if let Err(e) = result {
+                log::error!("Script for '{}' failed: {}", object_name, e);
+            }
+        }
+    }
+
+    /// Evaluates a one-off snippet against the same API, handy for debug consoles
+    pub fn eval(&self, source: &str) -> EngineResult<()> {
+        self.engine.run(source).map_err(|e| EngineError::Script { object: "eval".to_string(), message: e.to_string() })
+    }
+}
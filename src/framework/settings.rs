@@ -0,0 +1,153 @@
+use std::{collections::HashMap, fs, path::{Path, PathBuf}};
+use serde::{Deserialize, Serialize};
+
+use super::error::{EngineError, EngineResult};
+
+#[derive(Serialize, Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)] // Fields missing from the file fall back to their defaults
+pub struct Settings {
+    pub window_width: u32,
+    pub window_height: u32,
+    pub fullscreen: bool,
+    pub vsync: bool,
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub ui_volume: f32,
+    pub keybindings: HashMap<String, Vec<String>>, // Action name -> key names, as given by key_name
+    pub asset_root: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            // There is something holy about this resolution
+            window_width: 640,
+            window_height: 480,
+            fullscreen: false,
+            vsync: true,
+            master_volume: 1.0,
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            ui_volume: 1.0,
+            keybindings: HashMap::new(),
+            asset_root: "resources".to_string(),
+        }
+    }
+}
+
+impl Settings {
+    /// Reads settings from a JSON file, returning the defaults if the file doesn't exist
+    pub fn load(path: &Path) -> EngineResult<Self> {
+        if !path.exists() {
+            return Ok(Settings::default());
+        }
+
+        let contents = fs::read_to_string(path).map_err(|e| EngineError::io(path.display().to_string(), e))?;
+        serde_json::from_str(&contents).map_err(|e| EngineError::parse(path.display().to_string(), e))
+    }
+
+    pub fn save(&self, path: &Path) -> EngineResult<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| EngineError::io(parent.display().to_string(), e))?;
+        }
+
+        let contents = serde_json::to_string_pretty(self).map_err(EngineError::Serialize)?;
+        fs::write(path, contents).map_err(|e| EngineError::io(path.display().to_string(), e))
+    }
+}
+
+/// The name a GLFW key is stored under in the keybindings
+pub fn key_name(key: glfw::Key) -> String {
+    format!("{:?}", key)
+}
+
+/// Owns the settings loaded at startup and writes them back to disk whenever they change
+pub struct SettingsManager {
+    path: PathBuf,
+    settings: Settings,
+}
+
+impl SettingsManager {
+    /// Loads settings from the given file, using defaults if it is absent
+    pub fn new(path: PathBuf) -> EngineResult<Self> {
+        let settings = Settings::load(&path)?;
+        Ok(SettingsManager {
+            path,
+            settings,
+        })
+    }
+
+    pub fn get_settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// Applies a change to the settings and saves them if anything actually changed
+    pub fn modify<F: FnOnce(&mut Settings)>(&mut self, change: F) -> EngineResult<()> {
+        let previous = self.settings.clone();
+        change(&mut self.settings);
+
+        if self.settings != previous {
+            self.settings.save(&self.path)?;
+        }
+        Ok(())
+    }
+
+    /// Restores the defaults and saves them
+    pub fn reset_to_defaults(&mut self) -> EngineResult<()> {
+        self.modify(|settings| *settings = Settings::default())
+    }
+
+    /// Every action and the keys bound to it, sorted by action name for display in an options menu
+    pub fn list_bindings(&self) -> Vec<(String, Vec<String>)> {
+        let mut bindings: Vec<(String, Vec<String>)> = self.settings.keybindings.iter()
+            .map(|(action, keys)| (action.clone(), keys.clone()))
+            .collect();
+        bindings.sort_by(|a, b| a.0.cmp(&b.0));
+        bindings
+    }
+
+    pub fn get_bindings(&self, action: &str) -> Vec<String> {
+        self.settings.keybindings.get(action).cloned().unwrap_or_default()
+    }
+
+    /// Returns the action a key is bound to, if any
+    pub fn get_action_for_key(&self, key_name: &str) -> Option<String> {
+        self.settings.keybindings.iter()
+            .find(|(_, keys)| keys.iter().any(|key| key == key_name))
+            .map(|(action, _)| action.clone())
+    }
+
+    /// Binds a key to an action and saves. With replace, the action's other keys are dropped.
+    /// Fails without changing anything if the key is already bound to a different action.
+    pub fn rebind(&mut self, action: &str, key_name: &str, replace: bool) -> EngineResult<()> {
+        if let Some(existing_action) = self.get_action_for_key(key_name) {
+            if existing_action != action {
+                return Err(EngineError::KeyBindingConflict { key: key_name.to_owned(), action: existing_action });
+            }
+        }
+
+        self.modify(|settings| {
+            let keys = settings.keybindings.entry(action.to_owned()).or_default();
+            if replace {
+                keys.clear();
+            }
+            if !keys.iter().any(|key| key == key_name) {
+                keys.push(key_name.to_owned());
+            }
+        })
+    }
+
+    /// Removes a key from whatever action it is bound to and saves
+    pub fn unbind_key(&mut self, key_name: &str) -> EngineResult<()> {
+        self.modify(|settings| {
+            for keys in settings.keybindings.values_mut() {
+                keys.retain(|key| key != key_name);
+            }
+        })
+    }
+
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+}
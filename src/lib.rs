@@ -1 +1,38 @@
-pub mod framework;
\ No newline at end of file
+pub mod framework;
+
+// The types most applications need, so they don't have to reach into the module tree
+pub use framework::error::{EngineError, EngineResult};
+pub use framework::framework_controller::{FrameworkController, RenderMode, ScalingPolicy};
+pub use framework::asset_manager::{AssetManager, PakArchive};
+#[cfg(feature = "hot_reload")]
+pub use framework::asset_watcher::{AssetChange, AssetKind, AssetWatcher};
+pub use framework::framework_builder::{FrameworkBuilder, WindowContext};
+pub use framework::display::{DisplayManager, DisplayMode, MonitorInfo, VideoModeInfo};
+pub use framework::settings::{Settings, SettingsManager};
+pub use framework::game::Game;
+pub use framework::graphics::camera::Camera;
+pub use framework::graphics::texture_manager::{TextureFilter, TextureManager, TextureSettings};
+pub use framework::graphics::shader_manager::ShaderManager;
+pub use framework::graphics::background_loader::{BackgroundLoader, LoadedAsset, UploadedMesh};
+pub use framework::graphics::text::{BitmapFont, TextAlign, TextObject};
+pub use framework::graphics::particles::{ParticleConfig, ParticleEmitter};
+pub use framework::graphics::trail::{TrailConfig, TrailRenderer};
+pub use framework::graphics::tilemap::Tilemap;
+pub use framework::graphics::instanced::{Instance, InstanceId, InstancedGraphicsObject};
+pub use framework::graphics::debug_draw::DebugDraw;
+pub use framework::graphics::nine_slice::{NineSliceConfig, NineSliceObject};
+pub use framework::graphics::util::master_graphics_list::{MasterGraphicsList, NameCollisionPolicy};
+pub use framework::graphics::internal_object::{
+    animation_config::AnimationConfig,
+    atlas_config::AtlasConfig,
+    clip_rect::ClipRect,
+    custom_shader::CustomShader,
+    graphics_object::Generic2DGraphicsObject,
+    pivot::Pivot,
+    tiling_config::TilingConfig,
+    uniform::UniformValue,
+};
+
+// Re-exported so applications use the same windowing and GL versions as the framework
+pub use gl;
+pub use glfw;